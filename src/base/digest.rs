@@ -0,0 +1,53 @@
+//! Simple built-in checksums used to verify the integrity of a device tree.
+//!
+//! These are not cryptographic. For secure digests, feed
+//! [`DevTree::bytes_for_digest`](crate::base::DevTree::bytes_for_digest) into a hasher of your
+//! choice.
+
+/// A checksum algorithm supported by
+/// [`DevTree::verify_digest`](crate::base::DevTree::verify_digest).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DigestAlgo {
+    /// The CRC-32 used by zlib, Ethernet and others (reflected polynomial `0xedb88320`). The
+    /// digest is 4 bytes long, big-endian.
+    Crc32,
+    /// The 64-bit FNV-1a hash. The digest is 8 bytes long, big-endian.
+    Fnv1a64,
+}
+
+impl DigestAlgo {
+    /// Returns the length in bytes of digests produced by this algorithm.
+    #[must_use]
+    pub fn digest_len(self) -> usize {
+        match self {
+            DigestAlgo::Crc32 => 4,
+            DigestAlgo::Fnv1a64 => 8,
+        }
+    }
+
+    /// Computes the digest of `buf`, returned as a native integer.
+    pub(crate) fn compute(self, buf: &[u8]) -> u64 {
+        match self {
+            DigestAlgo::Crc32 => u64::from(crc32(buf)),
+            DigestAlgo::Fnv1a64 => fnv1a64(buf),
+        }
+    }
+}
+
+fn crc32(buf: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in buf {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xedb8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+fn fnv1a64(buf: &[u8]) -> u64 {
+    buf.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
@@ -9,6 +9,21 @@ pub enum DevTreeItem<'a, 'dt: 'a> {
     Prop(DevTreeProp<'a, 'dt>),
 }
 
+/// A structural event of a device tree, as returned by
+/// [`DevTree::structural_events`](crate::base::DevTree::structural_events).
+///
+/// Every [`StructEvent::Enter`] is balanced by a later [`StructEvent::Leave`], and properties
+/// belong to the most recently entered node which has not yet been left.
+#[derive(Clone, PartialEq)]
+pub enum StructEvent<'a, 'dt: 'a> {
+    /// A node was opened.
+    Enter(DevTreeNode<'a, 'dt>),
+    /// The most recently opened node was closed.
+    Leave,
+    /// A property of the currently open node.
+    Property(DevTreeProp<'a, 'dt>),
+}
+
 impl<'a, 'dt: 'a> UnwrappableDevTreeItem<'dt> for DevTreeItem<'a, 'dt> {
     type TreeNode = DevTreeNode<'a, 'dt>;
     type TreeProp = DevTreeProp<'a, 'dt>;
@@ -6,8 +6,8 @@ use core::str::from_utf8;
 
 use crate::prelude::*;
 
-use crate::base::parse::{next_devtree_token, ParsedTok};
-use crate::base::{DevTree, DevTreeItem, DevTreeNode, DevTreeProp};
+use crate::base::parse::{next_devtree_token_in, ParsedTok};
+use crate::base::{DevTree, DevTreeItem, DevTreeNode, DevTreeProp, StructEvent};
 use crate::error::{DevTreeError, Result};
 use crate::spec::fdt_reserve_entry;
 
@@ -18,7 +18,7 @@ pub use crate::common::prop::StringPropIter;
 use fallible_iterator::FallibleIterator;
 
 /// An iterator over [`fdt_reserve_entry`] objects within the FDT.
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 pub struct DevTreeReserveEntryIter<'a, 'dt: 'a> {
     offset: usize,
     fdt: &'a DevTree<'dt>,
@@ -54,7 +54,9 @@ impl<'a, 'dt: 'a> Iterator for DevTreeReserveEntryIter<'a, 'dt> {
     type Item = DevTreeReserveEntryRef<'dt>;
     fn next(&mut self) -> Option<Self::Item> {
         let next_offset = size_of::<fdt_reserve_entry>() + self.offset;
-        if next_offset > self.fdt.totalsize() {
+        // The reservation block ends where the structure block begins. Stop there even if the
+        // block is missing its terminating entry.
+        if next_offset > self.fdt.off_dt_struct() {
             None
         } else {
             // - We previously guarunteed enough memory with next_offset check.
@@ -75,8 +77,93 @@ impl<'a, 'dt: 'a> Iterator for DevTreeReserveEntryIter<'a, 'dt> {
     }
 }
 
+/// An iterator over the `(address, size)` regions of the memory reservation block which yields
+/// at most a fixed number of regions.
+///
+/// See [`DevTree::reserved_regions_bounded`].
+#[derive(Clone, Copy)]
+pub struct DevTreeReservedRegionIter<'a, 'dt: 'a> {
+    offset: usize,
+    remaining: usize,
+    fdt: &'a DevTree<'dt>,
+}
+
+impl<'a, 'dt: 'a> DevTreeReservedRegionIter<'a, 'dt> {
+    pub(crate) fn new(fdt: &'a DevTree<'dt>, max: usize) -> Self {
+        Self {
+            offset: fdt.off_mem_rsvmap(),
+            remaining: max,
+            fdt,
+        }
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeReservedRegionIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = (u64, u64);
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        // As with DevTreeReserveEntryIter, the block ends where the structure block begins.
+        let next_offset = size_of::<fdt_reserve_entry>() + self.offset;
+        if next_offset > self.fdt.off_dt_struct() {
+            return Ok(None);
+        }
+
+        let buf = self.fdt.buf();
+        let address = buf.read_be_u64(self.offset)?;
+        let size = buf.read_be_u64(self.offset + size_of::<u64>())?;
+        if address == 0 && size == 0 {
+            return Ok(None);
+        }
+        if self.remaining == 0 {
+            return Err(DevTreeError::LimitExceeded);
+        }
+        self.remaining -= 1;
+        self.offset = next_offset;
+        Ok(Some((address, size)))
+    }
+}
+
+/// An iterator over the NUL terminated strings of the device tree's strings block.
+///
+/// Each string is returned along with its offset from the start of the strings block, which is
+/// how properties refer to their names. See [`DevTree::strings`].
+#[derive(Clone, Copy)]
+pub struct DevTreeStringsBlockIter<'a, 'dt: 'a> {
+    offset: usize,
+    fdt: &'a DevTree<'dt>,
+}
+
+impl<'a, 'dt: 'a> DevTreeStringsBlockIter<'a, 'dt> {
+    pub(crate) fn new(fdt: &'a DevTree<'dt>) -> Self {
+        Self { offset: 0, fdt }
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeStringsBlockIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = (usize, &'dt str);
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        let block = self.fdt.strings_block()?;
+        if self.offset >= block.len() {
+            return Ok(None);
+        }
+
+        // Every string, including the last, must be terminated within the block.
+        let rest = &block[self.offset..];
+        let len = rest
+            .iter()
+            .position(|&b| b == 0)
+            .ok_or(DevTreeError::ParseError)?;
+        let offset = self.offset;
+        self.offset += len + 1;
+        Ok(Some((offset, from_utf8(&rest[..len])?)))
+    }
+}
+
 /// An iterator over all [`DevTreeItem`] objects.
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct DevTreeIter<'a, 'dt: 'a> {
     /// Offset of the last opened Device Tree Node.
     /// This is used to set properties' parent DevTreeNode.
@@ -91,7 +178,7 @@ pub struct DevTreeIter<'a, 'dt: 'a> {
     pub(crate) fdt: &'a DevTree<'dt>,
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct DevTreeNodeIter<'a, 'dt: 'a>(pub DevTreeIter<'a, 'dt>);
 impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodeIter<'a, 'dt> {
     type Item = DevTreeNode<'a, 'dt>;
@@ -101,7 +188,7 @@ impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodeIter<'a, 'dt> {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct DevTreePropIter<'a, 'dt: 'a>(pub DevTreeIter<'a, 'dt>);
 impl<'a, 'dt: 'a> FallibleIterator for DevTreePropIter<'a, 'dt> {
     type Error = DevTreeError;
@@ -111,7 +198,7 @@ impl<'a, 'dt: 'a> FallibleIterator for DevTreePropIter<'a, 'dt> {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct DevTreeNodePropIter<'a, 'dt: 'a>(pub DevTreeIter<'a, 'dt>);
 impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodePropIter<'a, 'dt> {
     type Error = DevTreeError;
@@ -121,7 +208,7 @@ impl<'a, 'dt: 'a> FallibleIterator for DevTreeNodePropIter<'a, 'dt> {
     }
 }
 
-#[derive(Clone, PartialEq)]
+#[derive(Clone, Copy, PartialEq)]
 pub struct DevTreeCompatibleNodeIter<'s, 'a, 'dt: 'a> {
     pub iter: DevTreeIter<'a, 'dt>,
     pub string: &'s str,
@@ -151,26 +238,45 @@ impl<'a, 'dt: 'a> DevTreeIter<'a, 'dt> {
         })
     }
 
-    pub fn last_node(mut self) -> Option<DevTreeNode<'a, 'dt>> {
+    pub fn last_node(self) -> Option<DevTreeNode<'a, 'dt>> {
+        self.try_last_node().unwrap()
+    }
+
+    /// Returns the last opened [`DevTreeNode`] (if one is open), or an [`Err`] if it cannot be
+    /// parsed.
+    pub fn try_last_node(mut self) -> Result<Option<DevTreeNode<'a, 'dt>>> {
         if let Some(off) = self.current_prop_parent_off.take() {
             self.offset = off.get();
-            return self.next_node().unwrap();
+            return self.next_node();
         }
-        None
+        Ok(None)
     }
 
     pub fn next_item(&mut self) -> Result<Option<DevTreeItem<'a, 'dt>>> {
+        let mut depth = 0;
+        self.next_item_tracking_depth(&mut depth)
+    }
+
+    /// Returns the next item, adjusting `depth` for every node opened or closed along the way.
+    ///
+    /// On return `depth` holds the depth of the returned node, or the depth of the node which
+    /// contains the returned property.
+    fn next_item_tracking_depth(
+        &mut self,
+        depth: &mut isize,
+    ) -> Result<Option<DevTreeItem<'a, 'dt>>> {
         loop {
             let old_offset = self.offset;
             // Safe because we only pass offsets which are returned by next_devtree_token.
-            let res = unsafe { next_devtree_token(self.fdt.buf(), &mut self.offset)? };
+            let res = unsafe { next_devtree_token_in(self.fdt, &mut self.offset)? };
 
             match res {
                 Some(ParsedTok::BeginNode(node)) => {
+                    *depth += 1;
                     self.current_prop_parent_off =
                         unsafe { Some(NonZeroUsize::new_unchecked(old_offset)) };
                     return Ok(Some(DevTreeItem::Node(DevTreeNode {
-                        parse_iter: self.clone(),
+                        parse_iter: *self,
                         name: from_utf8(node.name).map_err(|e| e.into()),
                     })));
                 }
@@ -191,6 +297,7 @@ impl<'a, 'dt: 'a> DevTreeIter<'a, 'dt> {
                     // The current node has ended.
                     // No properties may follow until the next node starts.
                     self.current_prop_parent_off = None;
+                    *depth -= 1;
                 }
                 Some(_) => continue,
                 None => return Ok(None),
@@ -198,6 +305,18 @@ impl<'a, 'dt: 'a> DevTreeIter<'a, 'dt> {
         }
     }
 
+    /// Skips the remainder of the most recently opened node, including all of its descendants.
+    fn skip_current_node(&mut self) -> Result<()> {
+        let mut parse_iter = DevTreeParseIter {
+            offset: self.offset,
+            fdt: self.fdt,
+        };
+        parse_iter.skip_current_subtree()?;
+        self.offset = parse_iter.offset;
+        self.current_prop_parent_off = None;
+        Ok(())
+    }
+
     pub fn next_prop(&mut self) -> Result<Option<DevTreeProp<'a, 'dt>>> {
         loop {
             match self.next() {
@@ -263,3 +382,139 @@ impl<'a, 'dt: 'a> FallibleIterator for DevTreeIter<'a, 'dt> {
         self.next_item()
     }
 }
+
+/// An iterator over all [`DevTreeItem`] objects paired with their depth in the tree.
+///
+/// The root node has a depth of `0`, its children a depth of `1`, and so on. Properties report
+/// the depth of the node which contains them.
+#[derive(Clone, Copy, PartialEq)]
+pub struct DevTreeDepthIter<'a, 'dt: 'a> {
+    iter: DevTreeIter<'a, 'dt>,
+    depth: isize,
+}
+
+impl<'a, 'dt: 'a> DevTreeDepthIter<'a, 'dt> {
+    pub(crate) fn new(fdt: &'a DevTree<'dt>) -> Self {
+        Self {
+            iter: DevTreeIter::new(fdt),
+            depth: -1,
+        }
+    }
+
+    /// Creates an iterator which continues from a [`DevTreeNode`]'s position in the tree. Depths
+    /// are reported relative to that node, so its children have a depth of `1`.
+    pub(crate) fn from_node_iter(iter: DevTreeIter<'a, 'dt>) -> Self {
+        Self { iter, depth: 0 }
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeDepthIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = (DevTreeItem<'a, 'dt>, isize);
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        let item = self.iter.next_item_tracking_depth(&mut self.depth)?;
+        Ok(item.map(|item| (item, self.depth)))
+    }
+}
+
+/// An iterator over the [`StructEvent`]s of a device tree.
+#[derive(Clone, PartialEq)]
+pub struct DevTreeStructEventIter<'a, 'dt: 'a> {
+    iter: DevTreeIter<'a, 'dt>,
+    depth: isize,
+    /// Number of nodes closed before `pending`, which are yet to be reported.
+    leaves: isize,
+    /// An item which was parsed but has not been reported yet.
+    pending: Option<DevTreeItem<'a, 'dt>>,
+    /// Set once the end of the device tree has been parsed.
+    done: bool,
+}
+
+impl<'a, 'dt: 'a> DevTreeStructEventIter<'a, 'dt> {
+    pub(crate) fn new(fdt: &'a DevTree<'dt>) -> Self {
+        Self {
+            iter: DevTreeIter::new(fdt),
+            depth: -1,
+            leaves: 0,
+            pending: None,
+            done: false,
+        }
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeStructEventIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = StructEvent<'a, 'dt>;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        if self.leaves == 0 && self.pending.is_none() && !self.done {
+            let old_depth = self.depth;
+            let item = self.iter.next_item_tracking_depth(&mut self.depth)?;
+
+            // The depth reported for a node includes the node itself.
+            self.leaves = match item {
+                Some(DevTreeItem::Node(_)) => old_depth + 1 - self.depth,
+                _ => old_depth - self.depth,
+            };
+            self.done = item.is_none();
+            self.pending = item;
+        }
+
+        if self.leaves > 0 {
+            self.leaves -= 1;
+            return Ok(Some(StructEvent::Leave));
+        }
+        Ok(self.pending.take().map(|item| match item {
+            DevTreeItem::Node(node) => StructEvent::Enter(node),
+            DevTreeItem::Prop(prop) => StructEvent::Property(prop),
+        }))
+    }
+}
+
+/// An iterator over all [`DevTreeNode`] objects at or above a maximum depth.
+///
+/// The root node has a depth of `0`. The subtrees of nodes at the maximum depth are skipped
+/// without yielding their nodes.
+#[derive(Clone, Copy, PartialEq)]
+pub struct DevTreeMaxDepthNodeIter<'a, 'dt: 'a> {
+    iter: DevTreeIter<'a, 'dt>,
+    depth: isize,
+    max_depth: isize,
+}
+
+impl<'a, 'dt: 'a> DevTreeMaxDepthNodeIter<'a, 'dt> {
+    pub(crate) fn new(fdt: &'a DevTree<'dt>, max_depth: isize) -> Self {
+        Self {
+            iter: DevTreeIter::new(fdt),
+            depth: -1,
+            max_depth,
+        }
+    }
+}
+
+impl<'a, 'dt: 'a> FallibleIterator for DevTreeMaxDepthNodeIter<'a, 'dt> {
+    type Error = DevTreeError;
+    type Item = DevTreeNode<'a, 'dt>;
+
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        loop {
+            match self.iter.next_item_tracking_depth(&mut self.depth)? {
+                Some(DevTreeItem::Node(node)) => {
+                    let depth = self.depth;
+                    if depth >= self.max_depth {
+                        // Prune this node's subtree, the node itself is closed along with it.
+                        self.iter.skip_current_node()?;
+                        self.depth -= 1;
+                        if depth > self.max_depth {
+                            continue;
+                        }
+                    }
+                    return Ok(Some(node));
+                }
+                Some(DevTreeItem::Prop(_)) => continue,
+                None => return Ok(None),
+            }
+        }
+    }
+}
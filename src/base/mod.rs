@@ -62,10 +62,15 @@
 //! }
 //! ```
 
+#[doc(hidden)]
+pub mod digest;
 #[doc(hidden)]
 pub mod item;
 #[doc(hidden)]
 pub mod node;
+#[cfg(any(feature = "alloc", feature = "std"))]
+#[doc(hidden)]
+pub mod owned;
 #[doc(hidden)]
 pub mod prop;
 #[doc(hidden)]
@@ -74,10 +79,15 @@ pub mod tree;
 pub mod iters;
 pub mod parse;
 
+#[doc(inline)]
+pub use digest::*;
 #[doc(inline)]
 pub use item::*;
 #[doc(inline)]
 pub use node::*;
+#[cfg(any(feature = "alloc", feature = "std"))]
+#[doc(inline)]
+pub use owned::*;
 #[doc(inline)]
 pub use prop::*;
 #[doc(inline)]
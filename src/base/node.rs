@@ -1,9 +1,13 @@
 #[cfg(doc)]
 use super::*;
 
-use crate::base::iters::{DevTreeIter, DevTreeNodePropIter};
+use crate::base::iters::{DevTreeDepthIter, DevTreeIter, DevTreeNodePropIter};
+use crate::base::{DevTreeItem, DevTreeProp};
+use crate::common::node::Node;
 use crate::error::Result;
 
+use fallible_iterator::FallibleIterator;
+
 /// A handle to a Device Tree Node within the device tree.
 #[derive(Clone)]
 pub struct DevTreeNode<'a, 'dt: 'a> {
@@ -27,7 +31,60 @@ impl<'a, 'dt: 'a> DevTreeNode<'a, 'dt> {
     /// Returns an iterator over this node's children [`DevTreeProp`]
     #[must_use]
     pub fn props(&self) -> DevTreeNodePropIter<'a, 'dt> {
-        DevTreeNodePropIter(self.parse_iter.clone())
+        DevTreeNodePropIter(self.parse_iter)
+    }
+
+    /// Returns the number of direct children of this node.
+    ///
+    /// Without an index this requires parsing this node's entire subtree, so it runs in
+    /// `O(subtree)` time. Prefer the [`DevTreeIndex`](crate::index::DevTreeIndex) for repeated
+    /// structural queries.
+    pub fn child_count(&self) -> Result<usize> {
+        let mut iter = DevTreeDepthIter::from_node_iter(self.parse_iter);
+        let mut count = 0;
+        while let Some((item, depth)) = iter.next()? {
+            if let DevTreeItem::Node(_) = item {
+                match depth {
+                    1 => count += 1,
+                    d if d <= 0 => break,
+                    _ => continue,
+                }
+            }
+        }
+        Ok(count)
+    }
+
+    /// Returns the parent of this node, or [`None`] if this is the root node.
+    ///
+    /// Nodes do not record their parent, so this re-parses the device tree from its root and runs
+    /// in `O(n)` time. Prefer the [`DevTreeIndex`](crate::index::DevTreeIndex), which finds a
+    /// node's parent in `O(1)`, when performance matters.
+    pub fn parent(&self) -> Result<Option<DevTreeNode<'a, 'dt>>> {
+        let fdt = self.parse_iter.fdt;
+
+        // Find this node's depth, then find the last node one level up which precedes it.
+        let mut iter = DevTreeDepthIter::new(fdt);
+        let depth = loop {
+            match iter.next()? {
+                Some((DevTreeItem::Node(node), depth)) if node == *self => break depth,
+                Some(_) => continue,
+                None => return Ok(None),
+            }
+        };
+
+        let mut parent = None;
+        let mut iter = DevTreeDepthIter::new(fdt);
+        while let Some((item, d)) = iter.next()? {
+            if let DevTreeItem::Node(node) = item {
+                if node == *self {
+                    break;
+                }
+                if d == depth - 1 {
+                    parent = Some(node);
+                }
+            }
+        }
+        Ok(parent)
     }
 
     /// Returns the next [`DevTreeNode`] object with the provided compatible device tree property
@@ -40,6 +97,22 @@ impl<'a, 'dt: 'a> DevTreeNode<'a, 'dt> {
     ///
     /// TODO
     pub fn find_next_compatible_node(&self, string: &str) -> Result<Option<DevTreeNode<'a, 'dt>>> {
-        self.parse_iter.clone().next_compatible_node(string)
+        let mut iter = self.parse_iter;
+        iter.next_compatible_node(string)
+    }
+}
+
+impl<'a, 'dt: 'a> Node<'dt> for DevTreeNode<'a, 'dt> {
+    type NodeProp = DevTreeProp<'a, 'dt>;
+    type NodePropIter = DevTreeNodePropIter<'a, 'dt>;
+
+    #[inline]
+    fn name(&self) -> Result<&'dt str> {
+        self.name
+    }
+
+    #[inline]
+    fn props(&self) -> Self::NodePropIter {
+        DevTreeNodePropIter(self.parse_iter)
     }
 }
@@ -0,0 +1,106 @@
+//! A device tree which owns its buffer.
+//!
+//! This module is only available with the `alloc` or `std` feature enabled.
+
+use alloc::boxed::Box;
+use alloc::vec;
+use core::mem::size_of;
+use core::slice;
+
+use crate::base::tree::CachedHeader;
+use crate::base::DevTree;
+use crate::error::Result;
+
+enum Storage {
+    Bytes(Box<[u8]>),
+    /// A u32 aligned copy of a buffer which was not itself aligned, along with its length in
+    /// bytes.
+    Words(Box<[u32]>, usize),
+}
+
+impl Storage {
+    /// Copies `buf` into an aligned buffer.
+    fn copy_of(buf: &[u8]) -> Self {
+        let mut words = vec![0u32; buf.len().div_ceil(size_of::<u32>())];
+        // Safety: The words span at least buf.len() bytes.
+        unsafe {
+            slice::from_raw_parts_mut(words.as_mut_ptr() as *mut u8, buf.len())
+                .copy_from_slice(buf);
+        }
+        Storage::Words(words.into_boxed_slice(), buf.len())
+    }
+
+    fn as_bytes(&self) -> &[u8] {
+        match self {
+            Storage::Bytes(buf) => buf,
+            // Safety: The words span at least len bytes.
+            Storage::Words(words, len) => unsafe {
+                slice::from_raw_parts(words.as_ptr() as *const u8, *len)
+            },
+        }
+    }
+}
+
+/// A device tree which owns the buffer it was read from, e.g. a file read into a [`Vec`].
+///
+/// This avoids keeping the buffer alive alongside a borrowing [`DevTree`]. Use
+/// [`OwnedDevTree::devtree`] to parse it.
+///
+/// [`Vec`]: alloc::vec::Vec
+pub struct OwnedDevTree {
+    storage: Storage,
+    /// The header fields decoded when the device tree was validated.
+    header: CachedHeader,
+}
+
+impl OwnedDevTree {
+    /// Takes ownership of `buf`, which must contain exactly one device tree.
+    ///
+    /// If `buf` is not 32-bit aligned, it is copied into an aligned buffer. The device tree is
+    /// validated as by [`DevTree::new`].
+    pub fn new(buf: Box<[u8]>) -> Result<Self> {
+        if buf.as_ptr() as usize % size_of::<u32>() == 0 {
+            Self::from_storage(Storage::Bytes(buf))
+        } else {
+            Self::from_slice(&buf)
+        }
+    }
+
+    /// Copies `buf`, which must contain exactly one device tree, into an aligned buffer.
+    ///
+    /// Unlike [`DevTree::new`], `buf` need not be 32-bit aligned, e.g. a device tree embedded
+    /// with [`include_bytes!`]. The device tree is validated as by [`DevTree::new`].
+    pub fn from_slice(buf: &[u8]) -> Result<Self> {
+        Self::from_storage(Storage::copy_of(buf))
+    }
+
+    fn from_storage(storage: Storage) -> Result<Self> {
+        // Safety: The storage is aligned, see Storage.
+        let header = unsafe { DevTree::new(storage.as_bytes())? }.cached_header();
+        Ok(Self { storage, header })
+    }
+
+    /// Returns the bytes of the device tree.
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        self.storage.as_bytes()
+    }
+
+    /// Returns a [`DevTree`] borrowing this buffer.
+    #[must_use]
+    pub fn devtree(&self) -> DevTree<'_> {
+        // Safety: The buffer is aligned and its header was decoded when this was constructed.
+        unsafe { DevTree::from_cached_header(self.as_bytes(), self.header) }
+    }
+}
+
+/// A clone copies the device tree into a new buffer, which is aligned even if the original was
+/// owned as is.
+impl Clone for OwnedDevTree {
+    fn clone(&self) -> Self {
+        Self {
+            storage: Storage::copy_of(self.as_bytes()),
+            header: self.header,
+        }
+    }
+}
@@ -9,7 +9,7 @@ use num_traits::FromPrimitive;
 use crate::base::DevTree;
 use crate::error::{DevTreeError, Result};
 use crate::priv_util::SliceRead;
-use crate::spec::{fdt_prop_header, FdtTok, MAX_NODE_NAME_LEN};
+use crate::spec::{fdt_prop_header, FdtTok};
 
 use fallible_iterator::FallibleIterator;
 
@@ -32,10 +32,45 @@ pub unsafe fn next_devtree_token<'a>(
     buf: &'a [u8],
     off: &mut usize,
 ) -> Result<Option<ParsedTok<'a>>> {
+    let tok_off = *off;
+    let res = read_devtree_token(buf, off);
+    if let Err(err) = &res {
+        log_debug!("Failed to parse token at offset {:#x}: {:?}", tok_off, err);
+    }
+    res
+}
+
+/// Tokenizes the structure block of `fdt` as by [`next_devtree_token`], additionally requiring
+/// the name of each property, including its NUL terminator, to lie within the strings block.
+///
+/// # Safety
+///
+/// `off` must be u32 aligned, see [`next_devtree_token`].
+pub(crate) unsafe fn next_devtree_token_in<'dt>(
+    fdt: &DevTree<'dt>,
+    off: &mut usize,
+) -> Result<Option<ParsedTok<'dt>>> {
+    let tok = next_devtree_token(fdt.buf(), off)?;
+    if let Some(ParsedTok::Prop(prop)) = &tok {
+        match fdt.strings_block()?.get(prop.name_offset..) {
+            Some(name) if name.contains(&0) => {}
+            _ => {
+                log_debug!(
+                    "Property name offset {:#x} is outside of the strings block",
+                    prop.name_offset
+                );
+                return Err(DevTreeError::ParseError);
+            }
+        }
+    }
+    Ok(tok)
+}
+
+unsafe fn read_devtree_token<'a>(buf: &'a [u8], off: &mut usize) -> Result<Option<ParsedTok<'a>>> {
     // These are guaranteed.
     // We only produce associated offsets that are aligned to 32 bits and within the buffer.
     debug_assert!(buf.as_ptr().add(*off) as usize % size_of::<u32>() == 0);
-    debug_assert!(buf.len() > (*off + size_of::<u32>()));
+    debug_assert!(buf.len() >= (*off + size_of::<u32>()));
 
     let fdt_tok_val = buf.unsafe_read_be_u32(*off)?;
     *off += size_of::<u32>();
@@ -43,7 +78,9 @@ pub unsafe fn next_devtree_token<'a>(
     match FromPrimitive::from_u32(fdt_tok_val) {
         Some(FdtTok::BeginNode) => {
             // Read the name (or return an error if the device tree is incorrectly formatted).
-            let name = buf.nread_bstring0(*off, MAX_NODE_NAME_LEN - 1)?;
+            // Names are read up to their terminator. The specification limits the node-name
+            // component to MAX_NODE_NAME_LEN bytes, but unit addresses may make names longer.
+            let name = buf.read_bstring0(*off)?;
 
             // Move to the end of name (adding null byte).
             *off += name.len() + 1;
@@ -54,9 +91,13 @@ pub unsafe fn next_devtree_token<'a>(
         }
         Some(FdtTok::Prop) => {
             // Get the memory we'll use as the header
-            let header_slice = buf
-                .get(*off..*off + size_of::<fdt_prop_header>())
+            //
+            // Offset arithmetic is checked, a crafted header could otherwise wrap it around and
+            // slip past the bounds checks below.
+            let header_end = off
+                .checked_add(size_of::<fdt_prop_header>())
                 .ok_or(DevTreeError::ParseError)?;
+            let header_slice = buf.get(*off..header_end).ok_or(DevTreeError::ParseError)?;
             // Re-interpret the data as a fdt_header.
             //
             // We already checked length.
@@ -67,11 +108,10 @@ pub unsafe fn next_devtree_token<'a>(
             let prop_len = u32::from(header.len) as usize;
 
             // Move offset past prop header
-            *off += size_of::<fdt_prop_header>();
+            *off = header_end;
             // Create a slice using the offset
-            let prop_buf = buf
-                .get(*off..*off + prop_len)
-                .ok_or(DevTreeError::ParseError)?;
+            let prop_end = off.checked_add(prop_len).ok_or(DevTreeError::ParseError)?;
+            let prop_buf = buf.get(*off..prop_end).ok_or(DevTreeError::ParseError)?;
 
             // Move the offset past the prop data.
             *off += prop_buf.len();
@@ -82,7 +122,6 @@ pub unsafe fn next_devtree_token<'a>(
             if name_offset > buf.len() {
                 return Err(DevTreeError::ParseError);
             }
-            let name_offset = name_offset;
 
             Ok(Some(ParsedTok::Prop(ParsedProp {
                 prop_buf,
@@ -131,7 +170,13 @@ pub enum ParsedTok<'a> {
     Nop,
 }
 
-#[derive(Debug, PartialEq)]
+/// An opaque position within a device tree's structure block.
+///
+/// Obtained from [`DevTreeParseIter::position`] and restored with [`DevTreeParseIter::seek`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ParsePos(usize);
+
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct DevTreeParseIter<'r, 'dt: 'r> {
     pub offset: usize,
     pub fdt: &'r DevTree<'dt>,
@@ -144,6 +189,49 @@ impl<'r, 'dt: 'r> DevTreeParseIter<'r, 'dt> {
             fdt,
         }
     }
+
+    /// Returns the current position of this iterator, which may later be restored with
+    /// [`DevTreeParseIter::seek`].
+    #[must_use]
+    pub fn position(&self) -> ParsePos {
+        ParsePos(self.offset)
+    }
+
+    /// Restores a position previously returned by [`DevTreeParseIter::position`].
+    ///
+    /// The end of the structure block is a valid position, after which the iterator yields no
+    /// further tokens.
+    ///
+    /// If the position is not u32 aligned or does not lie within this tree's structure block
+    /// (e.g. it was taken from a different tree), an [`Err`] containing
+    /// [`DevTreeError::InvalidOffset`] is returned and the iterator is left unchanged.
+    pub fn seek(&mut self, pos: ParsePos) -> Result<()> {
+        let start = self.fdt.off_dt_struct();
+        let end = start + self.fdt.size_dt_struct() as usize;
+        if pos.0 % size_of::<u32>() != 0 || pos.0 < start || pos.0 > end {
+            return Err(DevTreeError::InvalidOffset);
+        }
+        self.offset = pos.0;
+        Ok(())
+    }
+
+    /// Advances this iterator past the [`ParsedTok::EndNode`] which balances the most recently
+    /// returned [`ParsedTok::BeginNode`].
+    ///
+    /// This must only be called immediately after a [`ParsedTok::BeginNode`] was returned. If the
+    /// structure block ends before the subtree is closed, an [`Err`] is returned.
+    pub fn skip_current_subtree(&mut self) -> Result<()> {
+        let mut depth = 1usize;
+        while depth > 0 {
+            match self.next()? {
+                Some(ParsedTok::BeginNode(_)) => depth += 1,
+                Some(ParsedTok::EndNode) => depth -= 1,
+                Some(_) => continue,
+                None => return Err(DevTreeError::ParseError),
+            }
+        }
+        Ok(())
+    }
 }
 
 impl<'dt, 'a: 'dt> FallibleIterator for DevTreeParseIter<'dt, 'a> {
@@ -151,8 +239,13 @@ impl<'dt, 'a: 'dt> FallibleIterator for DevTreeParseIter<'dt, 'a> {
     type Item = ParsedTok<'a>;
 
     fn next(&mut self) -> Result<Option<Self::Item>> {
+        // Nothing follows the end of a (non-empty) structure block.
+        let size = self.fdt.size_dt_struct() as usize;
+        if size != 0 && self.offset == self.fdt.off_dt_struct() + size {
+            return Ok(None);
+        }
         // Safe because we're passing an unmodified (by us) offset.
         // next_devtree_token guaruntees alignment and out-of-bounds won't occur.
-        unsafe { next_devtree_token(self.fdt.buf(), &mut self.offset) }
+        unsafe { next_devtree_token_in(self.fdt, &mut self.offset) }
     }
 }
@@ -2,10 +2,9 @@ use core::ptr;
 
 use crate::base::iters::DevTreeIter;
 use crate::base::{DevTree, DevTreeNode};
+use crate::error::{DevTreeError, Result};
 use crate::prelude::*;
 
-use unsafe_unwrap::UnsafeUnwrap;
-
 /// A handle to a [`DevTreeNode`]'s Device Tree Property
 #[derive(Clone)]
 pub struct DevTreeProp<'a, 'dt: 'a> {
@@ -41,13 +40,14 @@ impl<'r, 'dt: 'r> PropReader<'dt> for DevTreeProp<'r, 'dt> {
     }
 
     /// Returns the node which this property is attached to
-    #[must_use]
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parent node cannot be parsed. See [`DevTreeProp::try_node`] for a
+    /// non-panicking alternative.
     fn node(&self) -> DevTreeNode<'r, 'dt> {
-        unsafe {
-            // Unsafe unwrap okay.
-            // We're look back in the tree - our parent node is behind us.
-            self.parent_iter.clone().last_node().unsafe_unwrap()
-        }
+        self.try_node()
+            .expect("Unable to parse the node containing this property.")
     }
 }
 
@@ -63,4 +63,13 @@ impl<'a, 'dt: 'a> DevTreeProp<'a, 'dt> {
             nameoff,
         }
     }
+
+    /// Returns the node which this property is attached to, or an [`Err`] if that node cannot be
+    /// parsed.
+    pub fn try_node(&self) -> Result<DevTreeNode<'a, 'dt>> {
+        // Our parent node is behind us in the tree, so it should always be found.
+        self.parent_iter
+            .try_last_node()?
+            .ok_or(DevTreeError::ParseError)
+    }
 }
@@ -1,32 +1,39 @@
-#[cfg(doc)]
 use crate::base::parse::ParsedTok;
 #[cfg(doc)]
 use crate::base::*;
 
 use core::mem::size_of;
+use core::ops::{ControlFlow, Range};
 use core::ptr;
 use core::slice;
 
-use crate::error::{DevTreeError, Result};
+use crate::error::{DevTreeError, FdtBlock, Result};
 
 use crate::priv_util::SliceRead;
-use crate::spec::{fdt_header, FDT_MAGIC};
+use crate::spec::{fdt_header, fdt_reserve_entry, FDT_MAGIC};
 
 use fallible_iterator::FallibleIterator;
 
 use super::iters::{
-    DevTreeCompatibleNodeIter, DevTreeIter, DevTreeNodeIter, DevTreeParseIter, DevTreePropIter,
-    DevTreeReserveEntryIter,
+    DevTreeCompatibleNodeIter, DevTreeDepthIter, DevTreeIter, DevTreeMaxDepthNodeIter,
+    DevTreeNodeIter, DevTreeParseIter, DevTreePropIter, DevTreeReserveEntryIter,
+    DevTreeReservedRegionIter, DevTreeStringsBlockIter, DevTreeStructEventIter,
 };
-use super::DevTreeNode;
+use super::{DevTreeItem, DevTreeNode, DigestAlgo};
 
 const fn is_aligned<T>(offset: usize) -> bool {
     offset % size_of::<T>() == 0
 }
 
-const fn verify_offset_aligned<T>(offset: usize) -> Result<usize> {
-    let i: [Result<usize>; 2] = [Err(DevTreeError::ParseError), Ok(offset)];
-    i[is_aligned::<T>(offset) as usize]
+fn verify_aligned<T>(addr: usize) -> Result<()> {
+    if is_aligned::<T>(addr) {
+        Ok(())
+    } else {
+        Err(DevTreeError::AlignmentError {
+            required: size_of::<T>(),
+            addr,
+        })
+    }
 }
 
 macro_rules! get_be32_field {
@@ -35,6 +42,18 @@ macro_rules! get_be32_field {
     };
 }
 
+/// A summary of a device tree's header, as returned by [`DevTree::summary`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DevTreeSummary {
+    pub magic: u32,
+    pub version: u32,
+    pub last_comp_version: u32,
+    pub boot_cpuid_phys: u32,
+    pub totalsize: usize,
+    /// The number of entries within the memory reservation block.
+    pub num_reserved: usize,
+}
+
 /// A parseable Flattened Device Tree.
 ///
 /// This parser was written according to the v0.3 specification provided at
@@ -42,6 +61,17 @@ macro_rules! get_be32_field {
 #[derive(Copy, Clone, Debug)]
 pub struct DevTree<'dt> {
     buf: &'dt [u8],
+    header: CachedHeader,
+}
+
+/// Header fields which are frequently used while parsing. These are decoded once on
+/// construction rather than re-read from the header on each use.
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct CachedHeader {
+    totalsize: usize,
+    off_dt_struct: usize,
+    off_dt_strings: usize,
+    size_dt_strings: usize,
 }
 
 impl<'dt> PartialEq for DevTree<'dt> {
@@ -52,6 +82,16 @@ impl<'dt> PartialEq for DevTree<'dt> {
 
 impl<'dt> DevTree<'dt> {
     pub const MIN_HEADER_SIZE: usize = size_of::<fdt_header>();
+    /// Returns true if `buf` begins with the device tree magic number.
+    ///
+    /// Unlike [`Self::verify_magic`], this is safe and places no alignment requirement on `buf`.
+    /// It's a cheap probe of whether a buffer might contain a device tree, the buffer must still
+    /// be validated by [`Self::new`].
+    #[must_use]
+    pub fn is_fdt(buf: &[u8]) -> bool {
+        buf.get(..4) == Some(&FDT_MAGIC.to_be_bytes()[..])
+    }
+
     /// Verify the magic header of a Device Tree buffer
     ///
     /// # Safety
@@ -77,7 +117,8 @@ impl<'dt> DevTree<'dt> {
     ///
     /// When parsing a FDT, it's possible that the actual size of the device tree may be unknown.
     /// For that reason, this method can be called before constructing the [`DevTree`]. For this
-    /// read to take place, the provided buffer must be at least [`Self::MIN_HEADER_SIZE`] long.
+    /// read to take place, the provided buffer must be at least [`Self::MIN_HEADER_SIZE`] long,
+    /// otherwise [`DevTreeError::InvalidParameter`] is returned.
     ///
     /// Once known, the user should resize the raw byte slice to this function's return value and
     /// pass that slice to [`DevTree::new()`].
@@ -97,8 +138,11 @@ impl<'dt> DevTree<'dt> {
     #[inline]
     pub unsafe fn read_totalsize(buf: &[u8]) -> Result<usize> {
         // Verify provided buffer alignment
-        verify_offset_aligned::<u32>(buf.as_ptr() as usize)
-            .map_err(|_| DevTreeError::InvalidParameter("Unaligned buffer provided"))?;
+        verify_aligned::<u32>(buf.as_ptr() as usize)?;
+
+        if buf.len() < Self::MIN_HEADER_SIZE {
+            return Err(DevTreeError::InvalidParameter("buffer smaller than header"));
+        }
 
         // Verify provided buffer magic
         Self::verify_magic(buf)?;
@@ -116,13 +160,51 @@ impl<'dt> DevTree<'dt> {
     /// - The passed buffer is exactly the length returned by [`Self::read_totalsize()`]
     #[inline]
     unsafe fn from_safe_slice(buf: &'dt [u8]) -> Result<Self> {
-        let ret = Self { buf };
+        let ret = Self {
+            buf,
+            header: CachedHeader {
+                totalsize: get_be32_field!(totalsize, fdt_header, buf)? as usize,
+                off_dt_struct: get_be32_field!(off_dt_struct, fdt_header, buf)? as usize,
+                off_dt_strings: get_be32_field!(off_dt_strings, fdt_header, buf)? as usize,
+                size_dt_strings: get_be32_field!(size_dt_strings, fdt_header, buf)? as usize,
+            },
+        };
+
+        // Versions before 16 include an extra field in each property, which this parser does not
+        // understand.
+        let version = ret.version();
+        if version < 16 {
+            return Err(DevTreeError::UnsupportedVersion(version));
+        }
+
         // Verify required alignment before returning.
-        verify_offset_aligned::<u32>(ret.off_mem_rsvmap())?;
-        verify_offset_aligned::<u32>(ret.off_dt_struct())?;
+        let addr = buf.as_ptr() as usize;
+        verify_aligned::<u32>(addr + ret.off_mem_rsvmap())?;
+        verify_aligned::<u32>(addr + ret.off_dt_struct())?;
         Ok(ret)
     }
 
+    /// Returns the header fields decoded when this was constructed, which may be used to
+    /// reconstruct it with [`Self::from_cached_header`].
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    #[inline]
+    pub(crate) fn cached_header(&self) -> CachedHeader {
+        self.header
+    }
+
+    /// Reconstructs a device tree from header fields decoded by an earlier construction, without
+    /// validating `buf` again.
+    ///
+    /// # Safety
+    ///
+    /// `buf` must be 32-bit aligned and hold the same bytes as the buffer `header` was decoded
+    /// from.
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    #[inline]
+    pub(crate) unsafe fn from_cached_header(buf: &'dt [u8], header: CachedHeader) -> Self {
+        Self { buf, header }
+    }
+
     /// Construct the parseable DevTree object from the provided byte slice.
     ///
     /// # Safety
@@ -131,6 +213,9 @@ impl<'dt> DevTree<'dt> {
     ///
     /// - The passed buffer is 32-bit aligned.
     /// - The passed buffer is exactly the length returned by [`Self::read_totalsize()`]
+    ///
+    /// Device trees older than version 16 are rejected with
+    /// [`DevTreeError::UnsupportedVersion`].
     #[inline]
     pub unsafe fn new(buf: &'dt [u8]) -> Result<Self> {
         if Self::read_totalsize(buf)? < buf.len() {
@@ -140,6 +225,25 @@ impl<'dt> DevTree<'dt> {
         }
     }
 
+    /// Construct the parseable DevTree object from a byte slice which may be larger than the
+    /// device tree it contains.
+    ///
+    /// The returned [`DevTree`] only spans the first `totalsize` bytes of the provided buffer.
+    ///
+    /// # Safety
+    ///
+    /// Callers of this method the must guarantee the following:
+    ///
+    /// - The passed buffer is 32-bit aligned.
+    #[inline]
+    pub unsafe fn new_in_larger_buffer(buf: &'dt [u8]) -> Result<Self> {
+        let size = Self::read_totalsize(buf)?;
+        let buf = buf.get(..size).ok_or(DevTreeError::InvalidParameter(
+            "Buffer is smaller than the device tree's totalsize",
+        ))?;
+        Self::from_safe_slice(buf)
+    }
+
     /// Construct the parseable DevTree object from a raw byte pointer
     ///
     /// # Safety
@@ -149,6 +253,8 @@ impl<'dt> DevTree<'dt> {
     /// - The passed address is 32-bit aligned.
     #[inline]
     pub unsafe fn from_raw_pointer(addr: *const u8) -> Result<Self> {
+        // Fail before reading anything through a misaligned pointer.
+        verify_aligned::<u32>(addr as usize)?;
         let buf: &[u8] = slice::from_raw_parts(addr, Self::MIN_HEADER_SIZE);
         let buf_size = Self::read_totalsize(buf)?;
         let buf: &[u8] = slice::from_raw_parts(addr, buf_size);
@@ -161,7 +267,7 @@ impl<'dt> DevTree<'dt> {
     #[inline]
     #[must_use]
     pub fn totalsize(&self) -> usize {
-        unsafe { get_be32_field!(totalsize, fdt_header, self.buf).unwrap() as usize }
+        self.header.totalsize
     }
 
     /// Returns the rsvmap offset field of the Device Tree
@@ -175,14 +281,14 @@ impl<'dt> DevTree<'dt> {
     #[inline]
     #[must_use]
     pub fn off_dt_struct(&self) -> usize {
-        unsafe { get_be32_field!(off_dt_struct, fdt_header, self.buf).unwrap() as usize }
+        self.header.off_dt_struct
     }
 
     /// Returns the dt_strings offset field of the Device Tree
     #[inline]
     #[must_use]
     pub fn off_dt_strings(&self) -> usize {
-        unsafe { get_be32_field!(off_dt_strings, fdt_header, self.buf).unwrap() as usize }
+        self.header.off_dt_strings
     }
 
     /// Returns the magic field of the Device Tree
@@ -217,7 +323,18 @@ impl<'dt> DevTree<'dt> {
     #[inline]
     #[must_use]
     pub fn size_dt_strings(&self) -> u32 {
-        unsafe { get_be32_field!(size_dt_strings, fdt_header, self.buf).unwrap() }
+        self.header.size_dt_strings as u32
+    }
+
+    /// Returns the strings block, or [`DevTreeError::InvalidLayout`] if the header places it
+    /// outside of the device tree's buffer.
+    #[inline]
+    pub(crate) fn strings_block(&self) -> Result<&'dt [u8]> {
+        let start = self.header.off_dt_strings;
+        start
+            .checked_add(self.header.size_dt_strings)
+            .and_then(|end| self.buf.get(start..end))
+            .ok_or(DevTreeError::InvalidLayout(FdtBlock::Strings))
     }
 
     /// Returns the size_dt_struct field of the Device Tree
@@ -227,6 +344,20 @@ impl<'dt> DevTree<'dt> {
         unsafe { get_be32_field!(size_dt_struct, fdt_header, self.buf).unwrap() }
     }
 
+    /// Returns a summary of the device tree's header fields and its number of memory
+    /// reservations, e.g. to be logged at boot.
+    #[must_use]
+    pub fn summary(&self) -> DevTreeSummary {
+        DevTreeSummary {
+            magic: self.magic(),
+            version: self.version(),
+            last_comp_version: self.last_comp_version(),
+            boot_cpuid_phys: self.boot_cpuid_phys(),
+            totalsize: self.totalsize(),
+            num_reserved: self.reserved_entries().count(),
+        }
+    }
+
     /// Returns a typed `*const T` to the given offset in the Device Tree buffer.
     ///
     /// # Safety
@@ -249,6 +380,27 @@ impl<'dt> DevTree<'dt> {
         DevTreeReserveEntryIter::new(self)
     }
 
+    /// Returns an iterator over the `(address, size)` regions of the memory reservation block,
+    /// yielding at most `max` regions.
+    ///
+    /// If the block's terminating entry is not reached within `max` regions, the iterator returns
+    /// an [`Err`] containing [`DevTreeError::LimitExceeded`]. Prefer this over
+    /// [`Self::reserved_entries`] when the device tree is untrusted.
+    #[must_use]
+    pub fn reserved_regions_bounded(&self, max: usize) -> DevTreeReservedRegionIter<'_, 'dt> {
+        DevTreeReservedRegionIter::new(self, max)
+    }
+
+    /// Returns an iterator over every string within the strings block, paired with its offset
+    /// from the start of the block.
+    ///
+    /// This walks the block independently of the structure block, which allows validating it
+    /// (e.g. that all strings are valid UTF-8) or mapping strings back to their offsets.
+    #[must_use]
+    pub fn strings(&self) -> DevTreeStringsBlockIter<'_, 'dt> {
+        DevTreeStringsBlockIter::new(self)
+    }
+
     /// Returns an iterator over [`DevTreeNode`] objects
     pub fn nodes(&self) -> DevTreeNodeIter<'_, 'dt> {
         DevTreeNodeIter(DevTreeIter::new(self))
@@ -264,6 +416,53 @@ impl<'dt> DevTree<'dt> {
         DevTreeIter::new(self)
     }
 
+    /// Returns an iterator over objects within the [`DevTreeItem`] enum paired with their depth
+    /// in the tree.
+    ///
+    /// The root node has a depth of `0`. Properties report the depth of the node which contains
+    /// them.
+    pub fn items_with_depth(&self) -> DevTreeDepthIter<'_, 'dt> {
+        DevTreeDepthIter::new(self)
+    }
+
+    /// Calls `f` with each node of the device tree and its depth, where the root node has a depth
+    /// of `0`.
+    ///
+    /// Nodes are visited in a single pass in depth-first order. Traversal stops early if `f`
+    /// returns [`ControlFlow::Break`], and errors returned by `f` are passed on to the caller.
+    pub fn for_each_node<F>(&self, mut f: F) -> Result<()>
+    where
+        F: FnMut(&DevTreeNode<'_, 'dt>, isize) -> Result<ControlFlow<()>>,
+    {
+        let mut iter = DevTreeDepthIter::new(self);
+        while let Some((item, depth)) = iter.next()? {
+            if let DevTreeItem::Node(node) = item {
+                if let ControlFlow::Break(()) = f(&node, depth)? {
+                    break;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns an iterator over the [`StructEvent`]s of the device tree, reporting when nodes are
+    /// entered and left.
+    ///
+    /// This is convenient when reconstructing the tree's structure from the base parser.
+    #[must_use]
+    pub fn structural_events(&self) -> DevTreeStructEventIter<'_, 'dt> {
+        DevTreeStructEventIter::new(self)
+    }
+
+    /// Returns an iterator over all nodes whose depth is at most `max_depth`, where the root node
+    /// has a depth of `0`.
+    ///
+    /// Deeper subtrees are skipped over rather than parsed into nodes.
+    #[must_use]
+    pub fn nodes_max_depth(&self, max_depth: isize) -> DevTreeMaxDepthNodeIter<'_, 'dt> {
+        DevTreeMaxDepthNodeIter::new(self, max_depth)
+    }
+
     /// Returns an iterator over low level parsing tokens, [`ParsedTok`].
     #[must_use]
     pub fn parse_iter(&self) -> DevTreeParseIter<'_, 'dt> {
@@ -286,6 +485,113 @@ impl<'dt> DevTree<'dt> {
         self.buf
     }
 
+    /// Returns exactly the `totalsize` bytes of the device tree blob, e.g. to forward it
+    /// unchanged to a guest or the next boot stage.
+    ///
+    /// Unlike [`Self::buf`], which returns the slice the device tree was constructed from, this
+    /// checks that the slice holds the entire blob and returns an [`Err`] containing
+    /// [`DevTreeError::InvalidParameter`] otherwise.
+    pub fn raw(&self) -> Result<&'dt [u8]> {
+        self.buf
+            .get(..self.header.totalsize)
+            .ok_or(DevTreeError::InvalidParameter(
+                "Buffer is smaller than the device tree's totalsize",
+            ))
+    }
+
+    /// Verifies that the first node of the device tree is an unnamed root node, as required by
+    /// the specification.
+    ///
+    /// Returns [`DevTreeError::NamedRootNode`] if the root node has a name, and
+    /// [`DevTreeError::ParseError`] if the tree does not begin with a node.
+    pub fn check_root(&self) -> Result<()> {
+        let mut iter = self.parse_iter();
+        while let Some(tok) = iter.next()? {
+            match tok {
+                ParsedTok::BeginNode(node) if node.name.is_empty() => return Ok(()),
+                ParsedTok::BeginNode(_) => return Err(DevTreeError::NamedRootNode),
+                ParsedTok::Nop => continue,
+                _ => break,
+            }
+        }
+        Err(DevTreeError::ParseError)
+    }
+
+    /// Verifies that the blocks described by the header are properly aligned, do not overlap, and
+    /// fit within the device tree's `totalsize`.
+    ///
+    /// Returns [`DevTreeError::InvalidLayout`] identifying the first inconsistent block.
+    pub fn check_layout(&self) -> Result<()> {
+        let totalsize = self.totalsize();
+        if totalsize < size_of::<fdt_header>() || totalsize > self.buf.len() {
+            return Err(DevTreeError::InvalidLayout(FdtBlock::Header));
+        }
+
+        // Returns the range of a block if it lies within the device tree.
+        let block = |off: usize, size: usize, align: usize, which: FdtBlock| {
+            let end = off.saturating_add(size);
+            if off < size_of::<fdt_header>() || end > totalsize || off % align != 0 {
+                return Err(DevTreeError::InvalidLayout(which));
+            }
+            Ok(off..end)
+        };
+
+        // The reservation block's length is only known by scanning for its terminating entry;
+        // require room for at least that entry.
+        let rsvmap = block(
+            self.off_mem_rsvmap(),
+            size_of::<fdt_reserve_entry>(),
+            8,
+            FdtBlock::MemReserve,
+        )?;
+        let structs = block(
+            self.off_dt_struct(),
+            self.size_dt_struct() as usize,
+            size_of::<u32>(),
+            FdtBlock::Struct,
+        )?;
+        let strings = block(
+            self.off_dt_strings(),
+            self.size_dt_strings() as usize,
+            1,
+            FdtBlock::Strings,
+        )?;
+
+        let overlaps = |a: &Range<usize>, b: &Range<usize>| a.start < b.end && b.start < a.end;
+        if overlaps(&rsvmap, &structs) || overlaps(&rsvmap, &strings) {
+            return Err(DevTreeError::InvalidLayout(FdtBlock::MemReserve));
+        }
+        if overlaps(&structs, &strings) {
+            return Err(DevTreeError::InvalidLayout(FdtBlock::Strings));
+        }
+        Ok(())
+    }
+
+    /// Returns the bytes of the device tree which should be digested to verify its integrity.
+    ///
+    /// This is the entire device tree, i.e. the first `totalsize` bytes of its buffer. Feed these
+    /// into a hasher of your choice when a cryptographic digest is required.
+    #[must_use]
+    pub fn bytes_for_digest(&self) -> &'dt [u8] {
+        &self.buf[..self.header.totalsize.min(self.buf.len())]
+    }
+
+    /// Verifies the device tree against an expected big-endian digest computed with the provided
+    /// built-in [`DigestAlgo`].
+    ///
+    /// Returns [`DevTreeError::InvalidParameter`] if `expected` is not of the algorithm's
+    /// [`digest_len`](DigestAlgo::digest_len).
+    pub fn verify_digest(&self, expected: &[u8], algo: DigestAlgo) -> Result<bool> {
+        if expected.len() != algo.digest_len() {
+            return Err(DevTreeError::InvalidParameter(
+                "Expected digest length does not match the digest algorithm",
+            ));
+        }
+
+        let digest = algo.compute(self.bytes_for_digest()).to_be_bytes();
+        Ok(digest[digest.len() - expected.len()..] == *expected)
+    }
+
     /// Returns the root [`DevTreeNode`] object of the device tree (if it exists).
     pub fn root(&self) -> Result<Option<DevTreeNode<'_, 'dt>>> {
         self.nodes().next()
@@ -1,2 +1,3 @@
 pub mod item;
+pub mod node;
 pub mod prop;
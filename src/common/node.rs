@@ -0,0 +1,41 @@
+use crate::prelude::*;
+
+use crate::error::{DevTreeError, Result};
+
+#[cfg(doc)]
+use crate::base::DevTreeNode;
+#[cfg(doc)]
+use crate::index::DevTreeIndexNode;
+
+/// Operations shared by all device tree node types.
+///
+/// This allows writing code which is generic over both [`DevTreeNode`] and [`DevTreeIndexNode`].
+pub trait Node<'dt> {
+    type NodeProp: PropReader<'dt>;
+    type NodePropIter: FallibleIterator<Item = Self::NodeProp, Error = DevTreeError>;
+
+    /// Returns the name of the node (including unit address tag)
+    fn name(&self) -> Result<&'dt str>;
+
+    /// Returns an iterator over this node's properties.
+    fn props(&self) -> Self::NodePropIter;
+
+    /// Returns the first of this node's properties with the provided name.
+    fn prop(&self, name: &str) -> Result<Option<Self::NodeProp>> {
+        self.props().find(|p| Ok(p.name()? == name))
+    }
+}
+
+/// Wraps an [`Iterator`] which cannot fail so that it may be used as a [`FallibleIterator`].
+#[derive(Clone, PartialEq)]
+pub struct InfallibleIter<I: Iterator>(pub I);
+
+impl<I: Iterator> FallibleIterator for InfallibleIter<I> {
+    type Item = I::Item;
+    type Error = DevTreeError;
+
+    #[inline]
+    fn next(&mut self) -> Result<Option<Self::Item>> {
+        Ok(self.0.next())
+    }
+}
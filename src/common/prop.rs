@@ -1,6 +1,9 @@
-use core::mem::size_of;
+use core::mem::{align_of, size_of};
+use core::slice;
 use core::str::from_utf8;
 
+use endian_type::types::u32_be;
+
 use crate::prelude::*;
 
 use crate::base::DevTree;
@@ -12,6 +15,25 @@ use crate::error::Result;
 #[cfg(doc)]
 use crate::base::DevTreeProp;
 
+/// The best-guess representation of a property's value, as returned by
+/// [`PropReader::guess_type`].
+///
+/// The flattened device tree format does not record the type of a property's value; this is
+/// a heuristic in the spirit of the one `dtc` uses when decompiling a blob.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PropType {
+    /// The property has no value (e.g. `dma-coherent;`).
+    Empty,
+    /// A single NUL terminated string.
+    String,
+    /// Multiple NUL terminated strings.
+    StringList,
+    /// A list of big-endian [`u32`] cells.
+    Cells,
+    /// Anything else.
+    Bytes,
+}
+
 pub trait PropReader<'dt> {
     type NodeType;
 
@@ -26,12 +48,18 @@ pub trait PropReader<'dt> {
     #[doc(hidden)]
     fn fdt(&self) -> &DevTree<'dt>;
 
+    /// Returns the raw bytes of the property's name, without validating them as UTF-8.
+    #[doc(hidden)]
+    #[inline]
+    fn name_bytes(&self) -> Result<&'dt [u8]> {
+        let str_offset = self.fdt().off_dt_strings() + self.nameoff();
+        Ok(self.fdt().buf().read_bstring0(str_offset)?)
+    }
+
     /// Returns the name of the property within the device tree.
     #[inline]
     fn name(&self) -> Result<&'dt str> {
-        let str_offset = self.fdt().off_dt_strings() + self.nameoff();
-        let name = self.fdt().buf().read_bstring0(str_offset)?;
-        Ok(from_utf8(name)?)
+        Ok(from_utf8(self.name_bytes()?)?)
     }
 
     /// Returns the length of the property value within the device tree
@@ -41,7 +69,10 @@ pub trait PropReader<'dt> {
         self.propbuf().len()
     }
 
-    /// Returns the node which this property is contained within.
+    /// Returns the node which owns this property, i.e. the node whose body this property is
+    /// defined in.
+    ///
+    /// This is neither the root of the tree nor the owning node's parent.
     fn node(&self) -> Self::NodeType;
 
     /// Read a big-endian [`u32`] from the provided offset in this device tree property's value.
@@ -60,6 +91,22 @@ pub trait PropReader<'dt> {
         }
     }
 
+    /// Read the big-endian [`u32`] cell at the provided cell index of this property's value.
+    ///
+    /// This is equivalent to [`PropReader::u32`], which also indexes by cell, but reads more
+    /// clearly for properties understood as cell arrays (e.g. `reg` or `interrupts`).
+    #[inline]
+    fn u32_at_cell(&self, cell_index: usize) -> Result<u32> {
+        self.u32(cell_index)
+    }
+
+    /// Returns the number of whole [`u32`] cells within this property's value.
+    #[inline]
+    #[must_use]
+    fn cell_count(&self) -> usize {
+        self.length() / size_of::<u32>()
+    }
+
     /// Read a big-endian [`u64`] from the provided offset in this device tree property's value.
     /// Convert the read value into the machines' native [`u64`] format and return it.
     ///
@@ -72,6 +119,75 @@ pub trait PropReader<'dt> {
             .or(Err(DevTreeError::InvalidOffset))
     }
 
+    /// Read a value made up of `cells` big-endian [`u32`] cells (e.g. an address described by
+    /// `#address-cells`) starting at the provided byte offset of this property's value.
+    ///
+    /// Returns the value along with the byte offset following the read cells. At most 2 cells
+    /// may be read, see [`PropReader::read_cells_u128`] for wider values.
+    #[inline]
+    fn read_cells(&self, offset: usize, cells: u32) -> Result<(u64, usize)> {
+        if cells > 2 {
+            return Err(DevTreeError::InvalidParameter(
+                "Cannot read more than 2 cells into a u64",
+            ));
+        }
+
+        // The truncation is lossless, at most 2 cells were read.
+        self.read_cells_u128(offset, cells)
+            .map(|(val, offset)| (val as u64, offset))
+    }
+
+    /// Read a value made up of `cells` big-endian [`u32`] cells starting at the provided byte
+    /// offset of this property's value.
+    ///
+    /// Returns the value along with the byte offset following the read cells. Up to 4 cells may
+    /// be read, which covers wide addresses such as PCI's `#address-cells = <3>`.
+    #[inline]
+    fn read_cells_u128(&self, offset: usize, cells: u32) -> Result<(u128, usize)> {
+        if cells > 4 {
+            return Err(DevTreeError::InvalidParameter(
+                "Cannot read more than 4 cells into a u128",
+            ));
+        }
+
+        let mut val = 0u128;
+        let mut offset = offset;
+        for _ in 0..cells {
+            let cell = self
+                .propbuf()
+                .read_be_u32(offset)
+                .or(Err(DevTreeError::InvalidOffset))?;
+            val = (val << 32) | u128::from(cell);
+            offset += size_of::<u32>();
+        }
+        Ok((val, offset))
+    }
+
+    /// Returns this property's value as a slice of big-endian cells without copying it.
+    ///
+    /// Property values are 4-byte aligned within the device tree and a [`DevTree`] requires a
+    /// 4-byte aligned buffer, so the returned slice is safe to index. Each cell may be converted
+    /// into a native [`u32`] with [`u32::from`].
+    ///
+    /// If the value's length is not a multiple of the cell size an [`Err`] containing
+    /// [`DevTreeError::ParseError`] will be returned.
+    #[inline]
+    fn cells_be(&self) -> Result<&'dt [u32_be]> {
+        let buf = self.propbuf();
+        if buf.len() % size_of::<u32_be>() != 0 || buf.as_ptr() as usize % align_of::<u32_be>() != 0
+        {
+            return Err(DevTreeError::ParseError);
+        }
+
+        // Safety: The buffer was verified to be aligned and a multiple of the cell size above.
+        unsafe {
+            Ok(slice::from_raw_parts(
+                buf.as_ptr() as *const u32_be,
+                buf.len() / size_of::<u32_be>(),
+            ))
+        }
+    }
+
     /// A Phandle is simply defined as a u32 value, as such this method performs the same action as
     /// [`self.u32`]
     #[inline]
@@ -94,6 +210,20 @@ pub trait PropReader<'dt> {
         self.iter_str().next()?.ok_or(DevTreeError::ParseError)
     }
 
+    /// Returns the property as a string, requiring the value to be exactly one NUL terminated
+    /// string.
+    ///
+    /// Unlike [`PropReader::str`], which stops at the first NUL, this returns
+    /// [`DevTreeError::ParseError`] if the terminator is not the last byte of the value (e.g.
+    /// `"okay\0garbage"`) or if the value is empty.
+    #[inline]
+    fn str_exact(&self) -> Result<&'dt str> {
+        match self.propbuf().split_last() {
+            Some((0, s)) if !s.contains(&0) => Ok(from_utf8(s)?),
+            _ => Err(DevTreeError::ParseError),
+        }
+    }
+
     /// Returns the property as a string fallible_iterator.
     /// # Safety
     ///
@@ -102,6 +232,52 @@ pub trait PropReader<'dt> {
     fn iter_str(&self) -> StringPropIter<'dt> {
         StringPropIter::new(self.propbuf())
     }
+    /// Copies the strings of a string list property into `list`, returning the total number of
+    /// strings within the property.
+    ///
+    /// If the property contains more strings than fit in `list`, only the first `list.len()`
+    /// strings are written. The returned count still includes every string, so a caller can
+    /// detect truncation by comparing it against `list.len()`. Passing an empty `list` simply
+    /// counts the strings.
+    fn get_strlist(&self, list: &mut [&'dt str]) -> Result<usize> {
+        let mut count = 0;
+        let mut iter = self.iter_str();
+        while let Some(s) = iter.next()? {
+            if let Some(slot) = list.get_mut(count) {
+                *slot = s;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    /// Returns a best-guess of the type of this property's value.
+    ///
+    /// Values made up of printable NUL terminated strings are considered strings. Otherwise
+    /// values whose length is a multiple of a cell are considered cells.
+    fn guess_type(&self) -> PropType {
+        let buf = self.propbuf();
+        if buf.is_empty() {
+            return PropType::Empty;
+        }
+
+        let is_string = buf[0] != 0
+            && buf[buf.len() - 1] == 0
+            && !buf.windows(2).any(|w| w == [0, 0])
+            && buf.iter().all(|&b| b == 0 || (b' '..=b'~').contains(&b));
+        if is_string {
+            if buf.iter().filter(|&&b| b == 0).count() == 1 {
+                PropType::String
+            } else {
+                PropType::StringList
+            }
+        } else if buf.len() % size_of::<u32>() == 0 {
+            PropType::Cells
+        } else {
+            PropType::Bytes
+        }
+    }
+
     /// Returns this property's data as a raw slice
     ///
     /// # Safety
@@ -111,6 +287,25 @@ pub trait PropReader<'dt> {
     fn raw(&self) -> &'dt [u8] {
         self.propbuf()
     }
+
+    /// Returns the remainder of this property's value, starting at the provided byte offset.
+    ///
+    /// An offset equal to the value's length yields an empty slice. If the offset is past the
+    /// end of the value an [`Err`] containing [`DevTreeError::InvalidOffset`] will be returned.
+    #[inline]
+    fn raw_from(&self, offset: usize) -> Result<&'dt [u8]> {
+        self.propbuf()
+            .get(offset..)
+            .ok_or(DevTreeError::InvalidOffset)
+    }
+}
+
+/// Returns true if the values of the two properties are byte-for-byte equal.
+///
+/// The properties may belong to different device trees. Their names are not compared.
+#[inline]
+pub fn props_value_eq<'a, 'b>(a: &impl PropReader<'a>, b: &impl PropReader<'b>) -> bool {
+    a.propbuf() == b.propbuf()
 }
 
 use fallible_iterator::FallibleIterator;
@@ -3,11 +3,36 @@
 #[cfg(doc)]
 use crate::index::DevTreeIndex;
 
+use crate::index::NodeHandle;
 use crate::priv_util::SliceReadError;
 use core::fmt;
 use core::result;
 use core::str::Utf8Error;
 
+/// A block of a flattened device tree, as laid out by its header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FdtBlock {
+    /// The header at the start of the device tree.
+    Header,
+    /// The memory reservation block.
+    MemReserve,
+    /// The structure block.
+    Struct,
+    /// The strings block.
+    Strings,
+}
+
+impl fmt::Display for FdtBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
+        match *self {
+            FdtBlock::Header => write!(f, "header"),
+            FdtBlock::MemReserve => write!(f, "memory reservation block"),
+            FdtBlock::Struct => write!(f, "structure block"),
+            FdtBlock::Strings => write!(f, "strings block"),
+        }
+    }
+}
+
 /// An error describe parsing problems when creating device trees.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DevTreeError {
@@ -30,6 +55,41 @@ pub enum DevTreeError {
 
     /// There wasn't enough memory to create a [`DevTreeIndex`].
     NotEnoughMemory,
+
+    /// The requested node or property does not exist within the device tree.
+    NotFound,
+
+    /// The root node has a name. The specification requires the root node's name to be empty.
+    NamedRootNode,
+
+    /// Two sibling nodes share the same name. The specification requires sibling names to be
+    /// unique.
+    ///
+    /// Identifies the later of the two siblings, which may be resolved with
+    /// [`DevTreeIndex::node_from_handle`].
+    DuplicateNodeName(NodeHandle),
+
+    /// The internal links of a [`DevTreeIndex`] are inconsistent. This likely indicates the
+    /// index's buffer was corrupted after it was built.
+    InvalidIndex(&'static str),
+
+    /// The offset or size of the given block, as recorded in the header, is misaligned, overlaps
+    /// another block, or does not fit within the device tree's `totalsize`.
+    InvalidLayout(FdtBlock),
+
+    /// The device tree's header reports a version older than 16, which lays out properties
+    /// differently and is not supported.
+    UnsupportedVersion(u32),
+
+    /// A buffer or block of the device tree does not begin at an address aligned to `required`
+    /// bytes.
+    AlignmentError {
+        required: usize,
+        addr: usize,
+    },
+
+    /// A bounded iteration reached its limit before the end of the items it was iterating over.
+    LimitExceeded,
 }
 
 impl From<SliceReadError> for DevTreeError {
@@ -47,6 +107,8 @@ impl From<Utf8Error> for DevTreeError {
 /// The result of a parse.
 pub type Result<T> = core::result::Result<T, DevTreeError>;
 
+// Offsets into the device tree, should any be included in a message, are written in hex (`{:#x}`)
+// so they may be compared directly against the output of tools such as `fdtdump`.
 impl fmt::Display for DevTreeError {
     fn fmt(&self, f: &mut fmt::Formatter) -> result::Result<(), fmt::Error> {
         match *self {
@@ -65,6 +127,57 @@ impl fmt::Display for DevTreeError {
                 f,
                 "Unable to fit device tree index into the provided buffer."
             ),
+            DevTreeError::NotFound => write!(f, "The requested item was not found."),
+            DevTreeError::NamedRootNode => write!(f, "Device tree root node has a name."),
+            DevTreeError::DuplicateNodeName(node) => write!(
+                f,
+                "Device tree node at offset {:#x} shares its name with a sibling.",
+                node.offset()
+            ),
+            DevTreeError::InvalidIndex(err) => {
+                write!(f, "Device tree index is inconsistent: {}", err)
+            }
+            DevTreeError::InvalidLayout(block) => {
+                write!(f, "Device tree {} is out of bounds or misplaced.", block)
+            }
+            DevTreeError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported device tree version {}.", version)
+            }
+            DevTreeError::AlignmentError { required, addr } => write!(
+                f,
+                "Address {:#x} is not aligned to {} bytes.",
+                addr, required
+            ),
+            DevTreeError::LimitExceeded => write!(f, "Iteration limit exceeded."),
         }
     }
 }
+
+#[cfg(feature = "std")]
+impl std::error::Error for DevTreeError {}
+
+#[cfg(feature = "std")]
+impl From<DevTreeError> for std::io::Error {
+    fn from(e: DevTreeError) -> std::io::Error {
+        use std::io::ErrorKind;
+
+        let kind = match e {
+            DevTreeError::InvalidParameter(_) | DevTreeError::AlignmentError { .. } => {
+                ErrorKind::InvalidInput
+            }
+            DevTreeError::InvalidMagicNumber
+            | DevTreeError::InvalidOffset
+            | DevTreeError::ParseError
+            | DevTreeError::StrError(_) => ErrorKind::InvalidData,
+            DevTreeError::NotEnoughMemory => ErrorKind::OutOfMemory,
+            DevTreeError::NamedRootNode
+            | DevTreeError::DuplicateNodeName(_)
+            | DevTreeError::InvalidIndex(_)
+            | DevTreeError::InvalidLayout(_)
+            | DevTreeError::LimitExceeded => ErrorKind::InvalidData,
+            DevTreeError::UnsupportedVersion(_) => ErrorKind::Unsupported,
+            DevTreeError::NotFound => ErrorKind::NotFound,
+        };
+        std::io::Error::new(kind, e)
+    }
+}
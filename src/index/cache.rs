@@ -0,0 +1,110 @@
+//! A caller owned cache of resolved node paths, see [`DevTreeIndex::node_at_path_cached`].
+
+use core::ptr;
+
+use crate::base::DigestAlgo;
+
+use super::tree::{path_component_matches, DTINode, DevTreeIndex};
+use super::DevTreeIndexNode;
+
+const CAPACITY: usize = 8;
+
+/// A small least-recently-used cache of paths resolved by
+/// [`DevTreeIndex::node_at_path_cached`].
+///
+/// The cache requires no allocation. It remembers the [`PathCache::CAPACITY`] most recently
+/// resolved paths as a hash of the path and the location of the node within the index.
+///
+/// A cache is bound to the first index it is used with. Using it with another index clears it.
+pub struct PathCache<'i, 'dt: 'i> {
+    root: *const DTINode<'i, 'dt>,
+    /// `(path hash, node offset from the root)` pairs, the most recently used first.
+    entries: [Option<(u64, usize)>; CAPACITY],
+}
+
+impl<'i, 'dt: 'i> PathCache<'i, 'dt> {
+    /// The number of paths remembered by a cache.
+    pub const CAPACITY: usize = CAPACITY;
+
+    /// Creates an empty cache.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            root: ptr::null(),
+            entries: [None; Self::CAPACITY],
+        }
+    }
+
+    /// Forgets all cached paths.
+    pub fn clear(&mut self) {
+        self.entries = [None; Self::CAPACITY];
+    }
+}
+
+impl<'i, 'dt: 'i> Default for PathCache<'i, 'dt> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returns true if `node` is found at `path` by [`DevTreeIndex::node_at_path`].
+fn node_matches_path(node: &DevTreeIndexNode<'_, '_, '_>, path: &str) -> bool {
+    let mut node = node.clone();
+    for component in path.rsplit('/').filter(|c| !c.is_empty()) {
+        let matches = match node.name() {
+            Ok(name) => path_component_matches(name, component),
+            Err(_) => false,
+        };
+        match node.parent() {
+            Some(parent) if matches => node = parent,
+            _ => return false,
+        }
+    }
+    path.starts_with('/') && node.parent().is_none()
+}
+
+impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
+    /// Like [`Self::node_at_path`], but first consults and then updates the provided
+    /// [`PathCache`].
+    ///
+    /// Repeated lookups of a recently resolved path take `O(depth)` time to confirm the cached
+    /// node rather than walking the tree from its root.
+    pub fn node_at_path_cached<'a>(
+        &'a self,
+        cache: &mut PathCache<'i, 'dt>,
+        path: &str,
+    ) -> Option<DevTreeIndexNode<'a, 'i, 'dt>> {
+        let root: &DTINode<'i, 'dt> = self.root().node;
+        if !ptr::eq(cache.root, root) {
+            cache.clear();
+            cache.root = root;
+        }
+
+        let hash = DigestAlgo::Fnv1a64.compute(path.as_bytes());
+        for i in 0..CAPACITY {
+            let offset = match cache.entries[i] {
+                Some((h, offset)) if h == hash => offset,
+                Some(_) => continue,
+                None => break,
+            };
+
+            // Safety: Offsets are only cached for nodes of the index whose root is `cache.root`.
+            // The cache shares the index's `'i` lifetime, so that memory may not have been reused.
+            let node = unsafe {
+                &*((root as *const DTINode as *const u8).wrapping_add(offset) as *const DTINode)
+            };
+            let node = DevTreeIndexNode::new(self, node);
+            if node_matches_path(&node, path) {
+                cache.entries[..=i].rotate_right(1);
+                return Some(node);
+            }
+        }
+
+        let node = self.node_at_path(path)?;
+        let offset =
+            (node.node as *const DTINode as usize).wrapping_sub(root as *const DTINode as usize);
+        cache.entries.rotate_right(1);
+        cache.entries[0] = Some((hash, offset));
+        Some(node)
+    }
+}
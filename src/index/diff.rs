@@ -0,0 +1,190 @@
+//! Comparison of two indexed device trees, e.g. a base tree and the same tree after an overlay
+//! was applied.
+
+use core::ops::ControlFlow;
+
+use crate::prelude::*;
+
+use crate::error::DevTreeError;
+
+use super::tree::DevTreeIndex;
+
+/// A difference between two device trees, as reported by [`DevTreeIndex::diff_against`].
+///
+/// Events describing the original tree (`self` of [`DevTreeIndex::diff_against`]) refer to its
+/// nodes and properties, events describing the other tree refer to the other tree's. The path of
+/// the affected node may be reconstructed by walking [`DevTreeIndexNode::parent`] and the name of
+/// an affected property is available through [`PropReader::name`].
+#[derive(Clone)]
+pub enum DiffEvent<'a, 'i: 'a, 'dt: 'i> {
+    /// A node (along with its entire subtree) only exists in the other tree.
+    NodeAdded(DevTreeIndexNode<'a, 'i, 'dt>),
+    /// A node (along with its entire subtree) only exists in the original tree.
+    NodeRemoved(DevTreeIndexNode<'a, 'i, 'dt>),
+    /// A property only exists in the other tree.
+    PropAdded(DevTreeIndexProp<'a, 'i, 'dt>),
+    /// A property only exists in the original tree.
+    PropRemoved(DevTreeIndexProp<'a, 'i, 'dt>),
+    /// A property exists in both trees, but with different values.
+    PropChanged {
+        old: DevTreeIndexProp<'a, 'i, 'dt>,
+        new: DevTreeIndexProp<'a, 'i, 'dt>,
+    },
+}
+
+fn find_prop<'a, 'i: 'a, 'dt: 'i>(
+    node: &DevTreeIndexNode<'a, 'i, 'dt>,
+    name: &str,
+) -> Option<DevTreeIndexProp<'a, 'i, 'dt>> {
+    node.props().find(|prop| prop.name() == Ok(name))
+}
+
+/// Passes `$event` to `$cb`, returning from the enclosing function if `$cb` asks to stop.
+macro_rules! emit {
+    ( $cb:expr, $event:expr ) => {
+        if let ControlFlow::Break(()) = $cb($event) {
+            return Ok(ControlFlow::Break(()));
+        }
+    };
+}
+
+/// Reports the differences between the properties of `old` and `new` and the children only
+/// present in one of them. Matching children are not descended into.
+fn diff_node<'a, 'i: 'a, 'dt: 'i, F>(
+    old: &DevTreeIndexNode<'a, 'i, 'dt>,
+    new: &DevTreeIndexNode<'a, 'i, 'dt>,
+    cb: &mut F,
+) -> Result<ControlFlow<()>, DevTreeError>
+where
+    F: FnMut(DiffEvent<'a, 'i, 'dt>) -> ControlFlow<()>,
+{
+    for prop in old.props() {
+        match find_prop(new, prop.name()?) {
+            None => emit!(cb, DiffEvent::PropRemoved(prop)),
+            Some(other) if !props_value_eq(&prop, &other) => emit!(
+                cb,
+                DiffEvent::PropChanged {
+                    old: prop,
+                    new: other,
+                }
+            ),
+            Some(_) => {}
+        }
+    }
+    for prop in new.props() {
+        if find_prop(old, prop.name()?).is_none() {
+            emit!(cb, DiffEvent::PropAdded(prop));
+        }
+    }
+
+    for child in old.children() {
+        if new.child_named(child.name()?).is_none() {
+            emit!(cb, DiffEvent::NodeRemoved(child));
+        }
+    }
+    for child in new.children() {
+        if old.child_named(child.name()?).is_none() {
+            emit!(cb, DiffEvent::NodeAdded(child));
+        }
+    }
+    Ok(ControlFlow::Continue(()))
+}
+
+/// Returns the first node of `candidates` with a counterpart among the children of `parent`,
+/// along with that counterpart.
+#[allow(clippy::type_complexity)]
+fn first_matching<'a, 'i: 'a, 'dt: 'i>(
+    candidates: impl Iterator<Item = DevTreeIndexNode<'a, 'i, 'dt>>,
+    parent: &DevTreeIndexNode<'a, 'i, 'dt>,
+) -> Result<Option<(DevTreeIndexNode<'a, 'i, 'dt>, DevTreeIndexNode<'a, 'i, 'dt>)>, DevTreeError> {
+    for candidate in candidates {
+        if let Some(other) = parent.child_named(candidate.name()?) {
+            return Ok(Some((candidate, other)));
+        }
+    }
+    Ok(None)
+}
+
+impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
+    /// Compares this device tree against `other`, calling `cb` once for each difference found.
+    ///
+    /// Nodes are matched by name beneath matching parents, properties are matched by name within
+    /// matching nodes and compared with [`props_value_eq`]. A node present in only one of the
+    /// trees is reported once; its descendants and properties are not reported separately.
+    ///
+    /// Differences are reported in depth-first order of the nodes present in both trees. This
+    /// neither recurses nor allocates, but takes `O(n * w)` time where `w` is the largest number
+    /// of children or properties of a node.
+    pub fn diff_against<'a, F>(
+        &'a self,
+        other: &'a DevTreeIndex<'i, 'dt>,
+        mut cb: F,
+    ) -> Result<(), DevTreeError>
+    where
+        F: FnMut(DiffEvent<'a, 'i, 'dt>),
+    {
+        // The callback never stops the comparison, so it always runs to completion.
+        self.diff_until(other, |event| {
+            cb(event);
+            ControlFlow::Continue(())
+        })
+        .map(|_| ())
+    }
+
+    /// Implements [`Self::diff_against`], stopping as soon as `cb` returns
+    /// [`ControlFlow::Break`]. Returns whether the comparison was stopped.
+    fn diff_until<'a, F>(
+        &'a self,
+        other: &'a DevTreeIndex<'i, 'dt>,
+        mut cb: F,
+    ) -> Result<ControlFlow<()>, DevTreeError>
+    where
+        F: FnMut(DiffEvent<'a, 'i, 'dt>) -> ControlFlow<()>,
+    {
+        let (mut old, mut new) = (self.root(), other.root());
+        if diff_node(&old, &new, &mut cb)?.is_break() {
+            return Ok(ControlFlow::Break(()));
+        }
+
+        // Walk the nodes present in both trees, following parent links back up rather than
+        // keeping a stack.
+        let mut next = first_matching(old.children(), &new)?;
+        loop {
+            match next {
+                Some((o, n)) => {
+                    old = o;
+                    new = n;
+                    if diff_node(&old, &new, &mut cb)?.is_break() {
+                        return Ok(ControlFlow::Break(()));
+                    }
+                    next = first_matching(old.children(), &new)?;
+                }
+                None => match (old.parent(), new.parent()) {
+                    (Some(old_parent), Some(new_parent)) => {
+                        next = first_matching(old.siblings().skip(1), &new_parent)?;
+                        old = old_parent;
+                        new = new_parent;
+                    }
+                    _ => return Ok(ControlFlow::Continue(())),
+                },
+            }
+        }
+    }
+
+    /// Returns true if this device tree and `other` describe the same tree.
+    ///
+    /// Trees are compared structurally as by [`Self::diff_against`]: nodes are matched by name
+    /// beneath matching parents and properties by name within matching nodes. The encoding of
+    /// the blobs is ignored, including `NOP` tokens, the layout of the strings block and the
+    /// order of sibling nodes or of a node's properties. Returns false if either tree cannot be
+    /// read.
+    ///
+    /// The comparison stops at the first difference found.
+    #[must_use]
+    pub fn semantically_eq(&self, other: &DevTreeIndex<'i, 'dt>) -> bool {
+        matches!(
+            self.diff_until(other, |_| ControlFlow::Break(())),
+            Ok(ControlFlow::Continue(()))
+        )
+    }
+}
@@ -1,7 +1,10 @@
+use core::mem::size_of;
 use core::ptr;
 
 use crate::prelude::*;
 
+use crate::error::DevTreeError;
+
 use super::tree::DTINode;
 use super::{DevTreeIndex, DevTreeIndexItem, DevTreeIndexNode, DevTreeIndexProp};
 
@@ -28,6 +31,370 @@ impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexNodeSiblingIter<'a, 'i, 'dt>
     }
 }
 
+/***********************************/
+/***********  Phandle Args  ********/
+/***********************************/
+
+/// A phandle reference decoded from a `<&provider arg0 arg1 ...>` style property.
+#[derive(Clone, PartialEq)]
+pub struct PhandleArgs<'a, 'i: 'a, 'dt: 'i> {
+    /// The provider node referenced by the phandle, or [`None`] for a placeholder entry whose
+    /// phandle is `0`.
+    pub node: Option<DevTreeIndexNode<'a, 'i, 'dt>>,
+    /// The raw big-endian argument cells which followed the phandle.
+    pub args: &'dt [u8],
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> PhandleArgs<'a, 'i, 'dt> {
+    /// Returns the number of argument cells which followed the phandle.
+    #[must_use]
+    pub fn num_args(&self) -> usize {
+        self.args.len() / size_of::<u32>()
+    }
+
+    /// Returns the argument cell at the provided index.
+    pub fn arg(&self, idx: usize) -> Result<u32, DevTreeError> {
+        self.args
+            .read_be_u32(idx * size_of::<u32>())
+            .or(Err(DevTreeError::InvalidOffset))
+    }
+}
+
+/// An iterator over the [`PhandleArgs`] entries of a property.
+///
+/// See [`DevTreeIndexNode::decode_phandle_list`].
+#[derive(Clone)]
+pub struct PhandleArgsIter<'s, 'a, 'i: 'a, 'dt: 'i> {
+    index: &'a DevTreeIndex<'i, 'dt>,
+    propbuf: &'dt [u8],
+    cells_prop: &'s str,
+    offset: usize,
+}
+
+impl<'s, 'a, 'i: 'a, 'dt: 'i> PhandleArgsIter<'s, 'a, 'i, 'dt> {
+    pub(super) fn new(
+        index: &'a DevTreeIndex<'i, 'dt>,
+        propbuf: &'dt [u8],
+        cells_prop: &'s str,
+    ) -> Self {
+        Self {
+            index,
+            propbuf,
+            cells_prop,
+            offset: 0,
+        }
+    }
+}
+
+impl<'s, 'a, 'i: 'a, 'dt: 'i> FallibleIterator for PhandleArgsIter<'s, 'a, 'i, 'dt> {
+    type Error = DevTreeError;
+    type Item = PhandleArgs<'a, 'i, 'dt>;
+
+    fn next(&mut self) -> Result<Option<Self::Item>, DevTreeError> {
+        if self.offset >= self.propbuf.len() {
+            return Ok(None);
+        }
+
+        let phandle = self.propbuf.read_be_u32(self.offset)?;
+        // A zero phandle is a placeholder which occupies a single cell and has no arguments.
+        if phandle == 0 {
+            self.offset += size_of::<u32>();
+            return Ok(Some(PhandleArgs {
+                node: None,
+                args: &[],
+            }));
+        }
+
+        let node = self
+            .index
+            .node_by_phandle(phandle)
+            .ok_or(DevTreeError::ParseError)?;
+
+        // The provider defines how many argument cells follow its phandle.
+        let num_cells = node
+            .try_prop(self.cells_prop)?
+            .ok_or(DevTreeError::ParseError)?
+            .u32(0)? as usize;
+
+        let start = self.offset + size_of::<u32>();
+        let end = num_cells
+            .checked_mul(size_of::<u32>())
+            .and_then(|len| len.checked_add(start))
+            .ok_or(DevTreeError::ParseError)?;
+        let args = self
+            .propbuf
+            .get(start..end)
+            .ok_or(DevTreeError::ParseError)?;
+
+        self.offset = end;
+        Ok(Some(PhandleArgs {
+            node: Some(node),
+            args,
+        }))
+    }
+}
+
+/***********************************/
+/***********  Ranges     ***********/
+/***********************************/
+
+/// Returns the value of the last (up to two) cells of `cells` as a [`u64`].
+fn low_u64(cells: &[u8]) -> u64 {
+    let start = cells.len().saturating_sub(size_of::<u64>());
+    cells[start..]
+        .iter()
+        .fold(0, |acc, &byte| (acc << 8) | u64::from(byte))
+}
+
+/// Returns the length in bytes of `cells` cells.
+fn cell_len(cells: u32) -> Result<usize, DevTreeError> {
+    (cells as usize)
+        .checked_mul(size_of::<u32>())
+        .ok_or(DevTreeError::ParseError)
+}
+
+/// Verifies that `propbuf` is made up of whole entries, each made up of fields of the provided
+/// lengths in bytes.
+fn verify_whole_entries(propbuf: &[u8], field_lens: &[usize]) -> Result<(), DevTreeError> {
+    let entry_len = field_lens
+        .iter()
+        .try_fold(0usize, |acc, &len| acc.checked_add(len))
+        .ok_or(DevTreeError::ParseError)?;
+    if !propbuf.is_empty() && (entry_len == 0 || propbuf.len() % entry_len != 0) {
+        return Err(DevTreeError::ParseError);
+    }
+    Ok(())
+}
+
+/// Returns the value of `cells` as a [`u128`], or `None` if it is made up of more than four
+/// cells.
+fn cells_u128(cells: &[u8]) -> Option<u128> {
+    if cells.len() > size_of::<u128>() {
+        return None;
+    }
+    Some(
+        cells
+            .iter()
+            .fold(0, |acc, &byte| (acc << 8) | u128::from(byte)),
+    )
+}
+
+/// A single entry of a bus node's `ranges` property.
+///
+/// Addresses which are made up of more than two cells (e.g. PCI's `#address-cells = <3>`) do not
+/// fit into a [`u64`]. For these, the [`u64`] fields hold the value of the last two cells. The
+/// full value of up to four cells is returned by the `_u128` methods, and values of any length
+/// are available through the raw cell slices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RangeEntry<'dt> {
+    /// The address within the child bus's address space.
+    pub child_addr: u64,
+    /// The address within the parent bus's address space.
+    pub parent_addr: u64,
+    /// The size of the range within the child bus's address space.
+    pub size: u64,
+    /// The raw big-endian cells of the child bus address.
+    pub child_cells: &'dt [u8],
+    /// The raw big-endian cells of the parent bus address.
+    pub parent_cells: &'dt [u8],
+    /// The raw big-endian cells of the size.
+    pub size_cells: &'dt [u8],
+}
+
+impl<'dt> RangeEntry<'dt> {
+    /// Returns the full child bus address, or `None` if it is made up of more than four cells.
+    #[must_use]
+    pub fn child_addr_u128(&self) -> Option<u128> {
+        cells_u128(self.child_cells)
+    }
+
+    /// Returns the full parent bus address, or `None` if it is made up of more than four cells.
+    #[must_use]
+    pub fn parent_addr_u128(&self) -> Option<u128> {
+        cells_u128(self.parent_cells)
+    }
+
+    /// Returns the full size, or `None` if it is made up of more than four cells.
+    #[must_use]
+    pub fn size_u128(&self) -> Option<u128> {
+        cells_u128(self.size_cells)
+    }
+}
+
+/// An iterator over the [`RangeEntry`] entries of a `ranges` property.
+///
+/// See [`DevTreeIndexNode::ranges`].
+#[derive(Clone, Debug)]
+pub struct RangesIter<'dt> {
+    propbuf: &'dt [u8],
+    child_len: usize,
+    parent_len: usize,
+    size_len: usize,
+}
+
+impl<'dt> RangesIter<'dt> {
+    /// Creates the iterator, verifying that `propbuf` is made up of whole entries of the provided
+    /// cell counts.
+    pub(super) fn new(
+        propbuf: &'dt [u8],
+        child_cells: u32,
+        parent_cells: u32,
+        size_cells: u32,
+    ) -> Result<Self, DevTreeError> {
+        let (child_len, parent_len, size_len) = (
+            cell_len(child_cells)?,
+            cell_len(parent_cells)?,
+            cell_len(size_cells)?,
+        );
+        verify_whole_entries(propbuf, &[child_len, parent_len, size_len])?;
+
+        Ok(Self {
+            propbuf,
+            child_len,
+            parent_len,
+            size_len,
+        })
+    }
+
+    /// Returns true if the `ranges` property is empty, meaning the child bus's address space is
+    /// identical to the parent's.
+    #[must_use]
+    pub fn is_identity(&self) -> bool {
+        self.propbuf.is_empty()
+    }
+}
+
+impl<'dt> Iterator for RangesIter<'dt> {
+    type Item = RangeEntry<'dt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.propbuf.is_empty() {
+            return None;
+        }
+
+        // Lengths were verified on construction, the buffer holds at least one whole entry.
+        let (child_cells, rest) = self.propbuf.split_at(self.child_len);
+        let (parent_cells, rest) = rest.split_at(self.parent_len);
+        let (size_cells, rest) = rest.split_at(self.size_len);
+        self.propbuf = rest;
+
+        Some(RangeEntry {
+            child_addr: low_u64(child_cells),
+            parent_addr: low_u64(parent_cells),
+            size: low_u64(size_cells),
+            child_cells,
+            parent_cells,
+            size_cells,
+        })
+    }
+}
+
+/***********************************/
+/***********  Reg        ***********/
+/***********************************/
+
+/// A single region of a device's `reg` property.
+///
+/// As with [`RangeEntry`], values made up of more than two cells are truncated to their last two
+/// cells. The full value is returned by [`RegEntry::address_u128`] and [`RegEntry::size_u128`],
+/// or is available through the raw cell slices.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RegEntry<'dt> {
+    /// The address of the region within the parent bus's address space.
+    pub address: u64,
+    /// The size of the region.
+    pub size: u64,
+    /// The raw big-endian cells of the address.
+    pub address_cells: &'dt [u8],
+    /// The raw big-endian cells of the size.
+    pub size_cells: &'dt [u8],
+}
+
+impl<'dt> RegEntry<'dt> {
+    /// Returns the full address, or `None` if it is made up of more than four cells.
+    #[must_use]
+    pub fn address_u128(&self) -> Option<u128> {
+        cells_u128(self.address_cells)
+    }
+
+    /// Returns the full size, or `None` if it is made up of more than four cells.
+    #[must_use]
+    pub fn size_u128(&self) -> Option<u128> {
+        cells_u128(self.size_cells)
+    }
+}
+
+/// An iterator over the [`RegEntry`] regions of a `reg` property.
+///
+/// See [`DevTreeIndexNode::reg`].
+#[derive(Clone, Debug)]
+pub struct RegIter<'dt> {
+    propbuf: &'dt [u8],
+    address_len: usize,
+    size_len: usize,
+}
+
+impl<'dt> RegIter<'dt> {
+    /// Creates the iterator, verifying that `propbuf` is made up of whole entries of the provided
+    /// cell counts.
+    pub(super) fn new(
+        propbuf: &'dt [u8],
+        address_cells: u32,
+        size_cells: u32,
+    ) -> Result<Self, DevTreeError> {
+        let (address_len, size_len) = (cell_len(address_cells)?, cell_len(size_cells)?);
+        verify_whole_entries(propbuf, &[address_len, size_len])?;
+
+        Ok(Self {
+            propbuf,
+            address_len,
+            size_len,
+        })
+    }
+}
+
+impl<'dt> Iterator for RegIter<'dt> {
+    type Item = RegEntry<'dt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.propbuf.is_empty() {
+            return None;
+        }
+
+        // Lengths were verified on construction, the buffer holds at least one whole entry.
+        let (address_cells, rest) = self.propbuf.split_at(self.address_len);
+        let (size_cells, rest) = rest.split_at(self.size_len);
+        self.propbuf = rest;
+
+        Some(RegEntry {
+            address: low_u64(address_cells),
+            size: low_u64(size_cells),
+            address_cells,
+            size_cells,
+        })
+    }
+}
+
+/// An iterator over all nodes which have a `reg` property, each paired with an iterator over
+/// its decoded regions.
+///
+/// Nodes whose `reg` property cannot be decoded yield the error encountered, see
+/// [`DevTreeIndexNode::reg`].
+#[derive(Clone)]
+pub struct DevTreeIndexRegNodeIter<'a, 'i: 'a, 'dt: 'i>(pub DevTreeIndexNodeIter<'a, 'i, 'dt>);
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexRegNodeIter<'a, 'i, 'dt> {
+    type Item = Result<(DevTreeIndexNode<'a, 'i, 'dt>, RegIter<'dt>), DevTreeError>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.find_map(|node| match node.reg_raw() {
+            Ok(Some((reg, address_cells, size_cells))) => {
+                Some(RegIter::new(reg, address_cells, size_cells).map(|reg| (node, reg)))
+            }
+            Ok(None) => None,
+            Err(err) => Some(Err(err)),
+        })
+    }
+}
+
 /***********************************/
 /***********  Items      ***********/
 /***********************************/
@@ -38,17 +405,21 @@ pub struct DevTreeIndexIter<'a, 'i: 'a, 'dt: 'i> {
     node: Option<&'a DTINode<'i, 'dt>>,
     prop_idx: usize,
     initial_node_returned: bool,
+    // If set, iteration ends upon reaching this node, the first to follow a subtree in DFS order.
+    subtree_end: Option<&'a DTINode<'i, 'dt>>,
 }
 
 impl<'a, 'i: 'a, 'dt: 'i> PartialEq for DevTreeIndexIter<'a, 'i, 'dt> {
     fn eq(&self, other: &Self) -> bool {
-        let cmp = match (self.node, other.node) {
+        let node_eq = |l: Option<&DTINode>, r: Option<&DTINode>| match (l, r) {
             (Some(l), Some(r)) => ptr::eq(l, r),
             (None, None) => true,
             _ => false,
         };
 
-        cmp && self.index == other.index
+        node_eq(self.node, other.node)
+            && node_eq(self.subtree_end, other.subtree_end)
+            && self.index == other.index
             && self.prop_idx == other.prop_idx
             && self.initial_node_returned == other.initial_node_returned
     }
@@ -74,6 +445,16 @@ impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexPropIter<'a, 'i, 'dt> {
 
 #[derive(Clone, PartialEq)]
 pub struct DevTreeIndexNodePropIter<'a, 'i: 'a, 'dt: 'i>(pub DevTreeIndexIter<'a, 'i, 'dt>);
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexNodePropIter<'a, 'i, 'dt> {
+    /// Creates an iterator over the properties of `node`, beginning at the property with the
+    /// provided index.
+    ///
+    /// Properties are stored contiguously within the index, so this takes `O(1)` time.
+    pub fn from_prop_idx(node: DevTreeIndexNode<'a, 'i, 'dt>, prop_idx: usize) -> Self {
+        Self(DevTreeIndexIter::from_prop_idx(node, prop_idx))
+    }
+}
+
 impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexNodePropIter<'a, 'i, 'dt> {
     type Item = DevTreeIndexProp<'a, 'i, 'dt>;
     fn next(&mut self) -> Option<Self::Item> {
@@ -81,6 +462,23 @@ impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexNodePropIter<'a, 'i, 'dt> {
     }
 }
 
+/// An iterator over the properties of a node, other than one particular property.
+///
+/// See [`DevTreeIndexProp::sibling_props`].
+#[derive(Clone)]
+pub struct DevTreeIndexSiblingPropIter<'a, 'i: 'a, 'dt: 'i> {
+    pub iter: DevTreeIndexNodePropIter<'a, 'i, 'dt>,
+    pub prop: DevTreeIndexProp<'a, 'i, 'dt>,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexSiblingPropIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexProp<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let prop = &self.prop;
+        self.iter.find(|p| p != prop)
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub struct DevTreeIndexCompatibleNodeIter<'s, 'a, 'i: 'a, 'dt: 'i> {
     pub iter: DevTreeIndexIter<'a, 'i, 'dt>,
@@ -93,6 +491,356 @@ impl<'s, 'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexCompatibleNodeIter<'s, 'a
     }
 }
 
+/// An iterator over all nodes which are compatible with any of a list of strings.
+///
+/// Each node is visited once, so a node matching several of the strings is only returned once.
+#[derive(Clone)]
+pub struct DevTreeIndexUniqueCompatibleNodeIter<'s, 'a, 'i: 'a, 'dt: 'i> {
+    pub iter: DevTreeIndexNodeIter<'a, 'i, 'dt>,
+    pub strings: &'s [&'s str],
+}
+impl<'s, 'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexUniqueCompatibleNodeIter<'s, 'a, 'i, 'dt> {
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let strings = self.strings;
+        self.iter
+            .find(|node| strings.iter().any(|s| node.is_compatible(s)))
+    }
+}
+
+/// An iterator over all nodes whose `device_type` property matches a given string.
+///
+/// Nodes without a `device_type` property are skipped.
+#[derive(Clone)]
+pub struct DevTreeIndexDeviceTypeNodeIter<'s, 'a, 'i: 'a, 'dt: 'i> {
+    pub iter: DevTreeIndexNodeIter<'a, 'i, 'dt>,
+    pub device_type: &'s str,
+}
+impl<'s, 'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexDeviceTypeNodeIter<'s, 'a, 'i, 'dt> {
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let device_type = self.device_type;
+        self.iter.find(|node| match node.try_prop("device_type") {
+            Ok(Some(prop)) => prop.str() == Ok(device_type),
+            _ => false,
+        })
+    }
+}
+
+/// An iterator over all [`DevTreeIndexItem`] objects paired with their depth in the tree.
+///
+/// The root node has a depth of `0`. Properties report the depth of the node which contains them.
+#[derive(Clone)]
+pub struct DevTreeIndexDepthIter<'a, 'i: 'a, 'dt: 'i> {
+    iter: DevTreeIndexIter<'a, 'i, 'dt>,
+    node: Option<&'a DTINode<'i, 'dt>>,
+    depth: usize,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexDepthIter<'a, 'i, 'dt> {
+    pub(super) fn new(index: &'a DevTreeIndex<'i, 'dt>) -> Self {
+        Self {
+            iter: DevTreeIndexIter::new(index),
+            node: None,
+            depth: 0,
+        }
+    }
+
+    fn has_parent(node: &DTINode<'i, 'dt>, parent: Option<&DTINode<'i, 'dt>>) -> bool {
+        match (node.parent(), parent) {
+            (Some(l), Some(r)) => ptr::eq(l, r),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    // Determine the depth of the next node in DFS order given the previous node and its depth.
+    //
+    // The next node is either our first child or a sibling of ourself or of one of our
+    // ancestors. Walking up only as far as needed keeps this amortized O(1).
+    fn next_depth(prev: &'a DTINode<'i, 'dt>, next: &'a DTINode<'i, 'dt>, depth: usize) -> usize {
+        if Self::has_parent(next, Some(prev)) {
+            return depth + 1;
+        }
+
+        let mut cur = prev;
+        let mut depth = depth;
+        while !Self::has_parent(next, cur.parent()) {
+            match cur.parent() {
+                Some(parent) => cur = parent,
+                None => break,
+            }
+            depth -= 1;
+        }
+        depth
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexDepthIter<'a, 'i, 'dt> {
+    type Item = (DevTreeIndexItem<'a, 'i, 'dt>, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.iter.next()?;
+        if let DevTreeIndexItem::Node(node) = &item {
+            if let Some(prev) = self.node {
+                self.depth = Self::next_depth(prev, node.node, self.depth);
+            }
+            self.node = Some(node.node);
+        }
+        Some((item, self.depth))
+    }
+}
+
+/// An iterator over all [`DevTreeIndexNode`] objects at a given depth in the tree, in DFS order.
+///
+/// The root node has a depth of `0`. Nodes deeper than the requested depth are skipped rather
+/// than visited. See [`DevTreeIndex::nodes_at_depth`].
+#[derive(Clone)]
+pub struct DevTreeIndexDepthNodeIter<'a, 'i: 'a, 'dt: 'i> {
+    index: &'a DevTreeIndex<'i, 'dt>,
+    /// The next node to visit along with its depth.
+    node: Option<(&'a DTINode<'i, 'dt>, usize)>,
+    depth: usize,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexDepthNodeIter<'a, 'i, 'dt> {
+    pub(super) fn new(index: &'a DevTreeIndex<'i, 'dt>, depth: usize) -> Self {
+        Self {
+            index,
+            node: Some((index.root().node, 0)),
+            depth,
+        }
+    }
+
+    // Returns the node following `node` in DFS order without descending past `self.depth`.
+    fn advance(
+        &self,
+        node: &'a DTINode<'i, 'dt>,
+        depth: usize,
+    ) -> Option<(&'a DTINode<'i, 'dt>, usize)> {
+        if depth < self.depth {
+            if let Some(child) = node.first_child() {
+                return Some((child, depth + 1));
+            }
+        }
+
+        let mut node = node;
+        let mut depth = depth;
+        while depth > 0 {
+            if let Some(sibling) = node.next_sibling() {
+                return Some((sibling, depth));
+            }
+            node = node.parent()?;
+            depth -= 1;
+        }
+        None
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexDepthNodeIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth)) = self.node {
+            self.node = self.advance(node, depth);
+            if depth == self.depth {
+                return Some(DevTreeIndexNode::new(self.index, node));
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over all enabled [`DevTreeIndexNode`] objects in DFS order.
+///
+/// When a disabled node is found, its entire subtree is skipped rather than visited. See
+/// [`DevTreeIndexNode::is_enabled`].
+#[derive(Clone)]
+pub struct DevTreeIndexEnabledNodeIter<'a, 'i: 'a, 'dt: 'i> {
+    index: &'a DevTreeIndex<'i, 'dt>,
+    node: Option<&'a DTINode<'i, 'dt>>,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexEnabledNodeIter<'a, 'i, 'dt> {
+    pub(super) fn new(index: &'a DevTreeIndex<'i, 'dt>) -> Self {
+        Self {
+            index,
+            node: Some(index.root().node),
+        }
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexEnabledNodeIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(node) = self.node {
+            let dti_node = DevTreeIndexNode::new(self.index, node);
+            if dti_node.is_enabled() {
+                self.node = node.next_dfs();
+                return Some(dti_node);
+            }
+            self.node = node.next_after_subtree();
+        }
+        None
+    }
+}
+
+/// An iterator over all [`DevTreeIndexNode`] objects in breadth-first order.
+///
+/// The index only links nodes in DFS order. Rather than keeping a queue of pending nodes (which
+/// would require an allocator), each level of the tree is found by scanning the tree in DFS
+/// order. Iterating over the entire tree takes `O(n * h)` time, where `h` is the height of the
+/// tree.
+#[derive(Clone)]
+pub struct DevTreeIndexBfsIter<'a, 'i: 'a, 'dt: 'i> {
+    index: &'a DevTreeIndex<'i, 'dt>,
+    node: Option<&'a DTINode<'i, 'dt>>,
+    depth: usize,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexBfsIter<'a, 'i, 'dt> {
+    pub(super) fn new(index: &'a DevTreeIndex<'i, 'dt>) -> Self {
+        Self {
+            index,
+            node: Some(index.root().node),
+            depth: 0,
+        }
+    }
+
+    // Returns the next node in DFS order after `from` which is at the `target` depth.
+    fn scan(
+        from: &'a DTINode<'i, 'dt>,
+        from_depth: usize,
+        target: usize,
+    ) -> Option<&'a DTINode<'i, 'dt>> {
+        let mut prev = from;
+        let mut depth = from_depth;
+        while let Some(next) = prev.next_dfs() {
+            depth = DevTreeIndexDepthIter::next_depth(prev, next, depth);
+            if depth == target {
+                return Some(next);
+            }
+            prev = next;
+        }
+        None
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexBfsIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexNode<'a, 'i, 'dt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.node?;
+        self.node = match Self::scan(node, self.depth, self.depth) {
+            Some(next) => Some(next),
+            None => {
+                // This level is exhausted, start the next one from the root.
+                self.depth += 1;
+                Self::scan(self.index.root().node, 0, self.depth)
+            }
+        };
+        Some(DevTreeIndexNode::new(self.index, node))
+    }
+}
+
+/// An iterator over every property with a given name within a node's subtree, paired with the
+/// node which owns it.
+///
+/// See [`DevTreeIndexNode::descendant_props_named`].
+#[derive(Clone)]
+pub struct DevTreeIndexDescendantPropIter<'s, 'a, 'i: 'a, 'dt: 'i> {
+    iter: DevTreeIndexIter<'a, 'i, 'dt>,
+    name: &'s str,
+}
+
+impl<'s, 'a, 'i: 'a, 'dt: 'i> DevTreeIndexDescendantPropIter<'s, 'a, 'i, 'dt> {
+    pub(super) fn new(root: &DevTreeIndexNode<'a, 'i, 'dt>, name: &'s str) -> Self {
+        Self {
+            iter: DevTreeIndexIter::subtree(root.clone()),
+            name,
+        }
+    }
+}
+
+impl<'s, 'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexDescendantPropIter<'s, 'a, 'i, 'dt> {
+    type Item = (DevTreeIndexNode<'a, 'i, 'dt>, DevTreeIndexProp<'a, 'i, 'dt>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some(prop) = self.iter.next_prop() {
+            if prop.name_bytes() == Ok(self.name.as_bytes()) {
+                return Some((prop.node(), prop));
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over the effective properties of a node.
+///
+/// See [`DevTreeIndexNode::effective_props`].
+#[derive(Clone)]
+pub struct DevTreeIndexEffectivePropIter<'a, 'i: 'a, 'dt: 'i> {
+    origin: DevTreeIndexNode<'a, 'i, 'dt>,
+    node: Option<DevTreeIndexNode<'a, 'i, 'dt>>,
+    prop_idx: usize,
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexEffectivePropIter<'a, 'i, 'dt> {
+    pub(super) fn new(origin: DevTreeIndexNode<'a, 'i, 'dt>) -> Self {
+        Self {
+            node: Some(origin.clone()),
+            origin,
+            prop_idx: 0,
+        }
+    }
+
+    /// Returns true if a node between the origin and the property's node also defines a property
+    /// of the same name.
+    fn is_shadowed(&self, prop: &DevTreeIndexProp<'a, 'i, 'dt>) -> bool {
+        let name = match prop.name() {
+            Ok(name) => name,
+            Err(_) => return false,
+        };
+
+        let mut node = Some(self.origin.clone());
+        while let Some(n) = node {
+            if ptr::eq(n.node, prop.node().node) {
+                break;
+            }
+            if n.props().any(|p| p.name() == Ok(name)) {
+                return true;
+            }
+            node = n.parent();
+        }
+        false
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Iterator for DevTreeIndexEffectivePropIter<'a, 'i, 'dt> {
+    type Item = DevTreeIndexProp<'a, 'i, 'dt>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let node = self.node.as_ref()?;
+            if self.prop_idx >= node.node.num_props {
+                self.node = node.parent();
+                self.prop_idx = 0;
+                continue;
+            }
+
+            // Unsafe OK, prop_idx is bounded by the node's number of props.
+            let prop = unsafe { node.node.prop_unchecked(self.prop_idx) };
+            let prop = DevTreeIndexProp::new(node.index(), node.node, prop);
+            self.prop_idx += 1;
+
+            if !self.is_shadowed(&prop) {
+                return Some(prop);
+            }
+        }
+    }
+}
+
 impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexIter<'a, 'i, 'dt> {
     pub(super) fn new(index: &'a DevTreeIndex<'i, 'dt>) -> Self {
         Self::from_node_include(index.root())
@@ -104,18 +852,34 @@ impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexIter<'a, 'i, 'dt> {
             node: None,
             prop_idx: 0,
             initial_node_returned: false,
+            subtree_end: None,
         }
     }
 
     /// Create an iterator from the current node.
     ///
-    /// The current node will be returned by the iterator.
+    /// The current node will be returned by the iterator. Iteration continues in DFS order past
+    /// the end of the node's subtree, on to its later siblings and those of its ancestors. Use
+    /// [`Self::subtree`] to only iterate over the node's subtree.
     pub fn from_node_include(node: DevTreeIndexNode<'a, 'i, 'dt>) -> Self {
         Self {
             index: node.index(),
             initial_node_returned: false,
             node: Some(node.node),
             prop_idx: 0,
+            subtree_end: None,
+        }
+    }
+
+    /// Create an iterator over the subtree of the current node.
+    ///
+    /// The current node is returned first, followed by its properties and then each of its
+    /// descendants in DFS order. Unlike [`Self::from_node_include`], iteration ends at the
+    /// boundary of the subtree rather than continuing on to later siblings.
+    pub fn subtree(node: DevTreeIndexNode<'a, 'i, 'dt>) -> Self {
+        Self {
+            subtree_end: node.node.next_after_subtree(),
+            ..Self::from_node_include(node)
         }
     }
 
@@ -125,9 +889,42 @@ impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexIter<'a, 'i, 'dt> {
             initial_node_returned: true,
             node: Some(node.node),
             prop_idx: 0,
+            subtree_end: None,
+        }
+    }
+
+    /// Create an iterator which begins at the property of `node` with the provided index.
+    ///
+    /// `node` itself will not be returned. If `prop_idx` is past the node's last property,
+    /// iteration begins with the item following the node's properties.
+    pub fn from_prop_idx(node: DevTreeIndexNode<'a, 'i, 'dt>, prop_idx: usize) -> Self {
+        Self {
+            index: node.index(),
+            initial_node_returned: true,
+            prop_idx: core::cmp::min(prop_idx, node.node.num_props),
+            node: Some(node.node),
+            subtree_end: None,
         }
     }
 
+    /// Returns the next item of this iterator without advancing it.
+    #[must_use]
+    pub fn peek(&self) -> Option<DevTreeIndexItem<'a, 'i, 'dt>> {
+        self.clone().next_devtree_item()
+    }
+
+    /// Returns the next node of this iterator without advancing it.
+    #[must_use]
+    pub fn peek_node(&self) -> Option<DevTreeIndexNode<'a, 'i, 'dt>> {
+        self.clone().next_node()
+    }
+
+    /// Returns the next property of this iterator without advancing it.
+    #[must_use]
+    pub fn peek_prop(&self) -> Option<DevTreeIndexProp<'a, 'i, 'dt>> {
+        self.clone().next_prop()
+    }
+
     pub fn next_sibling(&mut self) -> Option<DevTreeIndexNode<'a, 'i, 'dt>> {
         self.node.map(|node| {
             let cur = DevTreeIndexNode::new(self.index, node);
@@ -161,6 +958,11 @@ impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexIter<'a, 'i, 'dt> {
 
             // Otherwise move on to the next node.
             self.node = cur_node.next_dfs();
+            if let (Some(next), Some(end)) = (self.node, self.subtree_end) {
+                if ptr::eq(next, end) {
+                    self.node = None;
+                }
+            }
             self.node
                 .map(|cur_node| DevTreeIndexItem::Node(DevTreeIndexNode::new(self.index, cur_node)))
         })
@@ -67,12 +67,17 @@ pub mod prop;
 #[doc(hidden)]
 pub mod tree;
 
+pub mod cache;
+pub mod diff;
 pub mod iters;
+pub mod reloc;
+#[cfg(feature = "serde")]
+pub mod snapshot;
 
 #[doc(inline)]
 pub use item::DevTreeIndexItem;
 #[doc(inline)]
-pub use node::DevTreeIndexNode;
+pub use node::{DevTreeIndexNode, NodeHandle};
 #[doc(inline)]
 pub use prop::DevTreeIndexProp;
 #[doc(inline)]
@@ -1,9 +1,37 @@
+#[cfg(any(feature = "alloc", feature = "std"))]
+use alloc::collections::BTreeMap;
+use core::mem::size_of;
+use core::ops::Range;
 use core::ptr;
 use core::str::from_utf8;
 
-use super::iters::{DevTreeIndexIter, DevTreeIndexNodePropIter, DevTreeIndexNodeSiblingIter};
+use crate::prelude::*;
+
+use super::iters::{
+    DevTreeIndexDescendantPropIter, DevTreeIndexEffectivePropIter, DevTreeIndexIter,
+    DevTreeIndexNodePropIter, DevTreeIndexNodeSiblingIter, PhandleArgsIter, RangesIter, RegIter,
+};
 use super::tree::{DTINode, DevTreeIndex};
+use super::DevTreeIndexProp;
+use crate::common::node::InfallibleIter;
 use crate::error::DevTreeError;
+use crate::spec::NodeStatus;
+
+/// A reference to a node of a [`DevTreeIndex`] which does not borrow the index.
+///
+/// Handles may be stored freely and later resolved with [`DevTreeIndex::node_from_handle`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeHandle {
+    offset: usize,
+}
+
+impl NodeHandle {
+    /// Returns the offset of the node's `FDT_BEGIN_NODE` token within the device tree.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+}
 
 #[derive(Clone)]
 pub struct DevTreeIndexNode<'a, 'i: 'a, 'dt: 'i> {
@@ -30,6 +58,114 @@ impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexNode<'a, 'i, 'dt> {
         from_utf8(self.node.name).map_err(DevTreeError::StrError)
     }
 
+    /// Returns true if this node's `status` property is `"okay"` (or the legacy `"ok"`), or if
+    /// this node has no `status` property.
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        match self.try_prop("status") {
+            Ok(Some(status)) => matches!(
+                status.str().map(NodeStatus::from_value),
+                Ok(Some(NodeStatus::Okay))
+            ),
+            Ok(None) => true,
+            Err(_) => false,
+        }
+    }
+
+    /// Returns the range of bytes within the device tree buffer holding the value of this node's
+    /// `status` property.
+    ///
+    /// The index only borrows the device tree immutably. To change a node's status in place, drop
+    /// the index and overwrite this range of the original buffer with
+    /// [`NodeStatus::write_value`].
+    ///
+    /// Returns [`DevTreeError::NotFound`] if this node has no `status` property. Adding one
+    /// requires rewriting the device tree.
+    pub fn status_value_range(&self) -> Result<Range<usize>, DevTreeError> {
+        let status = self.try_prop("status")?.ok_or(DevTreeError::NotFound)?;
+        let start = status.raw().as_ptr() as usize - self.index.buf().as_ptr() as usize;
+        Ok(start..start + status.length())
+    }
+
+    /// Returns true if `compatible` is one of the strings of this node's `compatible` property.
+    ///
+    /// Any error encountered while reading the property is treated as a mismatch.
+    #[must_use]
+    pub fn is_compatible(&self, compatible: &str) -> bool {
+        self.compatible_rank(compatible).is_some()
+    }
+
+    /// Returns true if any string of `list` is one of the strings of this node's `compatible`
+    /// property, as when matching a node against a driver's table of supported devices.
+    ///
+    /// The property is only read once. Any error encountered while reading it is treated as a
+    /// mismatch.
+    #[must_use]
+    pub fn is_compatible_any(&self, list: &[&str]) -> bool {
+        match self.try_prop("compatible") {
+            Ok(Some(prop)) => prop
+                .iter_str()
+                .any(|s| Ok(list.contains(&s)))
+                .unwrap_or(false),
+            _ => false,
+        }
+    }
+
+    /// Returns the zero-based position of `compatible` within this node's `compatible`
+    /// property, or `None` if it is absent.
+    ///
+    /// Strings are listed from most to least specific, so a lower rank denotes a better match.
+    /// Any error encountered while reading the property is treated as a mismatch.
+    #[must_use]
+    pub fn compatible_rank(&self, compatible: &str) -> Option<usize> {
+        match self.try_prop("compatible") {
+            Ok(Some(prop)) => prop
+                .iter_str()
+                .position(|s| Ok(s == compatible))
+                .unwrap_or(None),
+            _ => None,
+        }
+    }
+
+    /// Returns the length of the value of this node's property named `name`, or `None` if it is
+    /// absent.
+    ///
+    /// This is useful for deciding how to interpret a value, e.g. whether a frequency is encoded
+    /// as one or two cells. Any error encountered while looking up the property is treated as
+    /// its absence.
+    #[must_use]
+    pub fn prop_len(&self, name: &str) -> Option<usize> {
+        match self.try_prop(name) {
+            Ok(Some(prop)) => Some(prop.length()),
+            _ => None,
+        }
+    }
+
+    /// Returns a [`NodeHandle`] which identifies this node without borrowing the index.
+    #[must_use]
+    pub fn to_handle(&self) -> NodeHandle {
+        // The node's name immediately follows its FDT_BEGIN_NODE token.
+        let name_offset =
+            self.node.name.as_ptr() as usize - self.index.fdt().buf().as_ptr() as usize;
+        NodeHandle {
+            offset: name_offset - size_of::<u32>(),
+        }
+    }
+
+    /// Returns the bytes of the device tree's structure block which make up this node and all of
+    /// its descendants, from its `FDT_BEGIN_NODE` token through its matching `FDT_END_NODE`
+    /// token.
+    ///
+    /// The end of each node is recorded when the index is built, so this does not parse the
+    /// device tree. Descendants left out of a filtered index (see
+    /// [`DevTreeIndex::new_filtered`]) are still included.
+    #[must_use]
+    pub fn subtree_bytes(&self) -> &'dt [u8] {
+        let buf = self.index.fdt().buf();
+        let start = self.node.name.as_ptr() as usize - buf.as_ptr() as usize - size_of::<u32>();
+        &buf[start..self.node.end]
+    }
+
     pub fn siblings(&self) -> DevTreeIndexNodeSiblingIter<'a, 'i, 'dt> {
         DevTreeIndexNodeSiblingIter::from(DevTreeIndexIter::from_node(self.clone()))
     }
@@ -38,6 +174,178 @@ impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexNode<'a, 'i, 'dt> {
         DevTreeIndexNodePropIter(DevTreeIndexIter::from_node(self.clone()))
     }
 
+    /// Returns an iterator over this node's properties, skipping the first `prop_idx` of them.
+    ///
+    /// This allows resuming iteration over a node's properties without re-walking them. See
+    /// [`DevTreeIndexNodePropIter::from_prop_idx`].
+    pub fn prop_iter_from(&self, prop_idx: usize) -> DevTreeIndexNodePropIter<'a, 'i, 'dt> {
+        DevTreeIndexNodePropIter::from_prop_idx(self.clone(), prop_idx)
+    }
+
+    /// Returns an iterator over this node's properties which borrows `self` rather than cloning
+    /// it.
+    pub fn props_borrowed(&self) -> impl Iterator<Item = DevTreeIndexProp<'a, 'i, 'dt>> + '_ {
+        (0..self.node.num_props).map(move |idx| {
+            // Unsafe OK, idx is bounded by the node's number of props.
+            let prop = unsafe { self.node.prop_unchecked(idx) };
+            DevTreeIndexProp::new(self.index, self.node, prop)
+        })
+    }
+
+    /// Returns an iterator over all children of this node's parent, including this node, in
+    /// order.
+    ///
+    /// The root node has no parent, so for the root only the root itself is returned.
+    pub fn siblings_including_self(&self) -> DevTreeIndexNodeSiblingIter<'a, 'i, 'dt> {
+        let first = self
+            .node
+            .parent()
+            .and_then(|parent| parent.first_child())
+            .unwrap_or(self.node);
+        DevTreeIndexNodeSiblingIter::from(DevTreeIndexIter::from_node_include(
+            DevTreeIndexNode::new(self.index, first),
+        ))
+    }
+
+    /// Returns this node's property with the provided name.
+    ///
+    /// `Ok(None)` means this node has no such property, while an [`Err`] means a property's name
+    /// could not be read from the device tree. Names are compared on their raw bytes, so names
+    /// which are not valid UTF-8 are treated as non-matching rather than as errors.
+    pub fn try_prop(
+        &self,
+        name: &str,
+    ) -> Result<Option<DevTreeIndexProp<'a, 'i, 'dt>>, DevTreeError> {
+        for prop in self.props() {
+            if prop.name_bytes()? == name.as_bytes() {
+                return Ok(Some(prop));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the first of this node's properties whose name matches `name`, ignoring ASCII
+    /// case.
+    ///
+    /// Property names are case-sensitive, so [`Self::try_prop`] should usually be preferred. This
+    /// tolerates trees authored with inconsistent casing. Properties whose names cannot be read
+    /// are skipped.
+    #[must_use]
+    pub fn prop_ignore_ascii_case(&self, name: &str) -> Option<DevTreeIndexProp<'a, 'i, 'dt>> {
+        self.props().find(
+            |prop| matches!(prop.name_bytes(), Ok(n) if n.eq_ignore_ascii_case(name.as_bytes())),
+        )
+    }
+
+    /// Returns a map from the name of each of this node's properties to its raw value.
+    ///
+    /// If several properties share a name, the last one is kept. This method is only available
+    /// with the `alloc` or `std` feature enabled.
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    pub fn prop_map(&self) -> Result<BTreeMap<&'dt str, &'dt [u8]>, DevTreeError> {
+        let mut map = BTreeMap::new();
+        for prop in self.props() {
+            map.insert(prop.name()?, prop.raw());
+        }
+        Ok(map)
+    }
+
+    /// Looks up several properties in a single pass over this node's properties.
+    ///
+    /// On success, `out[i]` holds the property named `names[i]`, or [`None`] if this node has no
+    /// such property. Returns [`DevTreeError::InvalidParameter`] if `out` and `names` differ in
+    /// length.
+    pub fn collect_props(
+        &self,
+        names: &[&str],
+        out: &mut [Option<DevTreeIndexProp<'a, 'i, 'dt>>],
+    ) -> Result<(), DevTreeError> {
+        if names.len() != out.len() {
+            return Err(DevTreeError::InvalidParameter(
+                "Output slice length must match the number of names",
+            ));
+        }
+
+        for slot in out.iter_mut() {
+            *slot = None;
+        }
+        for prop in self.props() {
+            let name = prop.name_bytes()?;
+            if let Some(i) = names.iter().position(|n| n.as_bytes() == name) {
+                out[i].get_or_insert(prop);
+            }
+        }
+        Ok(())
+    }
+
+    /// Invokes `f` with this node's property named `name`, returning its result, or [`None`] if
+    /// this node has no such property.
+    ///
+    /// Properties whose names cannot be read are treated as absent, see [`Self::try_prop`].
+    pub fn with_prop<R>(
+        &self,
+        name: &str,
+        f: impl FnOnce(&DevTreeIndexProp<'a, 'i, 'dt>) -> R,
+    ) -> Option<R> {
+        self.try_prop(name).ok().flatten().map(|prop| f(&prop))
+    }
+
+    /// Returns an iterator over every property named `name` within this node's subtree
+    /// (including this node itself), each paired with the node which owns it.
+    pub fn descendant_props_named<'s>(
+        &self,
+        name: &'s str,
+    ) -> DevTreeIndexDescendantPropIter<'s, 'a, 'i, 'dt> {
+        DevTreeIndexDescendantPropIter::new(self, name)
+    }
+
+    /// Returns an iterator over this node's properties whose names begin with `prefix` (e.g. a
+    /// vendor prefix such as `ti,`).
+    ///
+    /// Names are matched on their raw bytes before any UTF-8 validation. Properties whose names
+    /// cannot be read are skipped.
+    pub fn props_with_prefix<'s>(
+        &self,
+        prefix: &'s str,
+    ) -> impl Iterator<Item = DevTreeIndexProp<'a, 'i, 'dt>> + 's
+    where
+        'a: 's,
+    {
+        self.props().filter(
+            move |p| matches!(p.name_bytes(), Ok(name) if name.starts_with(prefix.as_bytes())),
+        )
+    }
+
+    /// Returns an iterator over every item of this node's subtree: this node, its properties,
+    /// and then each of its descendants (with their properties) in DFS order.
+    ///
+    /// Iteration ends once the subtree is exhausted.
+    pub fn subtree(&self) -> DevTreeIndexIter<'a, 'i, 'dt> {
+        DevTreeIndexIter::subtree(self.clone())
+    }
+
+    /// Returns the node which follows this node in DFS order.
+    pub fn next_in_dfs(&self) -> Option<Self> {
+        self.node.next_dfs().map(|next| Self::new(self.index, next))
+    }
+
+    /// Returns the node which precedes this node in DFS order.
+    ///
+    /// Nodes are only linked forwards, so this scans from the root of the tree and takes `O(n)`
+    /// time.
+    pub fn prev_in_dfs(&self) -> Option<Self> {
+        let mut prev: Option<&'a DTINode<'i, 'dt>> = None;
+        let mut cur = Some(self.index.root().node);
+        while let Some(node) = cur {
+            if ptr::eq(node, self.node) {
+                return prev.map(|prev| Self::new(self.index, prev));
+            }
+            prev = Some(node);
+            cur = node.next_dfs();
+        }
+        None
+    }
+
     pub fn parent(&self) -> Option<Self> {
         self.node.parent().map(|par| Self::new(self.index, par))
     }
@@ -51,6 +359,21 @@ impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexNode<'a, 'i, 'dt> {
         }
     }
 
+    /// Returns the first direct child of this node whose full name (including any unit address)
+    /// is `name`.
+    ///
+    /// Only this node's children are scanned, not its deeper descendants.
+    pub fn child_named(&self, name: &str) -> Option<Self> {
+        let mut child = self.node.first_child();
+        while let Some(node) = child {
+            if node.name == name.as_bytes() {
+                return Some(Self::new(self.index, node));
+            }
+            child = node.next_sibling();
+        }
+        None
+    }
+
     /// Returns true if `self` is a parent of the other [`DevTreeIndexNode`]
     pub fn is_parent_of(&self, other: &Self) -> bool {
         if let Some(parent) = &other.parent() {
@@ -63,4 +386,240 @@ impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexNode<'a, 'i, 'dt> {
     pub fn is_sibling_of(&self, other: &Self) -> bool {
         other.parent() == self.parent()
     }
+
+    /// Returns the lowest node which is an ancestor of both `self` and the other
+    /// [`DevTreeIndexNode`].
+    ///
+    /// A node is considered an ancestor of itself, so if `other` is a descendant of `self`, `self`
+    /// is returned. Returns `None` if the nodes belong to different indices.
+    pub fn common_ancestor(&self, other: &Self) -> Option<Self> {
+        if !ptr::eq(self.index, other.index) {
+            return None;
+        }
+
+        let depth = |mut node: &DTINode<'i, 'dt>| {
+            let mut depth = 0usize;
+            while let Some(parent) = node.parent() {
+                node = parent;
+                depth += 1;
+            }
+            depth
+        };
+
+        let (mut a, mut b) = (self.node, other.node);
+        let (mut a_depth, mut b_depth) = (depth(a), depth(b));
+
+        // Bring both nodes to the same depth, then step up in lockstep until they meet.
+        while a_depth > b_depth {
+            a = a.parent()?;
+            a_depth -= 1;
+        }
+        while b_depth > a_depth {
+            b = b.parent()?;
+            b_depth -= 1;
+        }
+        while !ptr::eq(a, b) {
+            a = a.parent()?;
+            b = b.parent()?;
+        }
+        Some(Self::new(self.index, a))
+    }
+
+    /// Returns the property named `name` of this node or, if this node does not define it, of
+    /// its nearest ancestor which does.
+    pub fn effective_prop(
+        &self,
+        name: &str,
+    ) -> Result<Option<DevTreeIndexProp<'a, 'i, 'dt>>, DevTreeError> {
+        let mut node = Some(self.clone());
+        while let Some(n) = node {
+            if let Some(prop) = Node::prop(&n, name)? {
+                return Ok(Some(prop));
+            }
+            node = n.parent();
+        }
+        Ok(None)
+    }
+
+    /// Returns an iterator over the effective properties of this node.
+    ///
+    /// This yields the properties of this node followed by those of each of its ancestors,
+    /// skipping any property whose name is already defined by a closer node.
+    pub fn effective_props(&self) -> DevTreeIndexEffectivePropIter<'a, 'i, 'dt> {
+        DevTreeIndexEffectivePropIter::new(self.clone())
+    }
+
+    /// Returns the value of a cell count property such as `#address-cells`, or `default` if this
+    /// node does not define it.
+    pub(super) fn cell_count(&self, prop: &str, default: u32) -> Result<u32, DevTreeError> {
+        match Node::prop(self, prop)? {
+            Some(p) => p.u32(0),
+            None => Ok(default),
+        }
+    }
+
+    /// Returns the value of a frequency property such as `clock-frequency` or
+    /// `timebase-frequency`, which may be encoded as either one or two cells.
+    ///
+    /// Returns `Ok(None)` if this node has no such property, or [`DevTreeError::ParseError`] if
+    /// the value is neither 4 nor 8 bytes long.
+    pub fn frequency(&self, prop: &str) -> Result<Option<u64>, DevTreeError> {
+        let prop = match Node::prop(self, prop)? {
+            Some(prop) => prop,
+            None => return Ok(None),
+        };
+        match prop.length() {
+            4 => Ok(Some(u64::from(prop.u32(0)?))),
+            8 => Ok(Some(prop.u64(0)?)),
+            _ => Err(DevTreeError::ParseError),
+        }
+    }
+
+    /// Returns an iterator over the entries of this node's `ranges` property.
+    ///
+    /// Child addresses and sizes are decoded using this node's `#address-cells` and
+    /// `#size-cells`, parent addresses using the parent node's `#address-cells`. An empty
+    /// `ranges` property denotes an identity mapping and yields no entries, see
+    /// [`RangesIter::is_identity`].
+    ///
+    /// Returns [`DevTreeError::NotFound`] if this node has no `ranges` property, meaning its
+    /// children's addresses are not translatable to the parent's address space.
+    pub fn ranges(&self) -> Result<RangesIter<'dt>, DevTreeError> {
+        let ranges = Node::prop(self, "ranges")?.ok_or(DevTreeError::NotFound)?;
+
+        // Defaults as defined by the devicetree specification.
+        let parent_cells = match self.parent() {
+            Some(parent) => parent.cell_count("#address-cells", 2)?,
+            None => 2,
+        };
+        RangesIter::new(
+            ranges.raw(),
+            self.cell_count("#address-cells", 2)?,
+            parent_cells,
+            self.cell_count("#size-cells", 1)?,
+        )
+    }
+
+    /// Returns the raw value of this node's `reg` property along with the parent node's
+    /// `#address-cells` and `#size-cells`, for decoding layouts not handled by [`Self::reg`].
+    ///
+    /// Returns `Ok(None)` if this node has no `reg` property.
+    #[allow(clippy::type_complexity)]
+    pub fn reg_raw(&self) -> Result<Option<(&'dt [u8], u32, u32)>, DevTreeError> {
+        let reg = match Node::prop(self, "reg")? {
+            Some(reg) => reg,
+            None => return Ok(None),
+        };
+
+        // Defaults as defined by the devicetree specification.
+        let (address_cells, size_cells) = match self.parent() {
+            Some(parent) if parent.parent().is_none() => (
+                self.index.root_address_cells()?,
+                self.index.root_size_cells()?,
+            ),
+            Some(parent) => (
+                parent.cell_count("#address-cells", 2)?,
+                parent.cell_count("#size-cells", 1)?,
+            ),
+            None => (2, 1),
+        };
+        Ok(Some((reg.raw(), address_cells, size_cells)))
+    }
+
+    /// Returns an iterator over the regions of this node's `reg` property.
+    ///
+    /// Addresses and sizes are decoded using the parent node's `#address-cells` and
+    /// `#size-cells`.
+    ///
+    /// Returns [`DevTreeError::NotFound`] if this node has no `reg` property.
+    pub fn reg(&self) -> Result<RegIter<'dt>, DevTreeError> {
+        let (reg, address_cells, size_cells) = self.reg_raw()?.ok_or(DevTreeError::NotFound)?;
+        RegIter::new(reg, address_cells, size_cells)
+    }
+
+    /// Finds the entry named `name` within a property indexed in parallel with a list of names,
+    /// such as `reg` with `reg-names` or `clocks` with `clock-names`.
+    ///
+    /// Returns the `values_prop` property along with the index of the named entry within it.
+    /// Decoding the entry is left to the caller since its size depends on the property. Returns
+    /// `Ok(None)` if either property is absent or `names_prop` does not contain `name`.
+    pub fn indexed_resource(
+        &self,
+        values_prop: &str,
+        names_prop: &str,
+        name: &str,
+    ) -> Result<Option<(DevTreeIndexProp<'a, 'i, 'dt>, usize)>, DevTreeError> {
+        let names = match self.try_prop(names_prop)? {
+            Some(names) => names,
+            None => return Ok(None),
+        };
+        let index = match names.iter_str().position(|s| Ok(s == name))? {
+            Some(index) => index,
+            None => return Ok(None),
+        };
+        Ok(self.try_prop(values_prop)?.map(|values| (values, index)))
+    }
+
+    /// Returns the `(address, size)` of the `reg` region named `name` by this node's `reg-names`
+    /// property.
+    ///
+    /// Returns `Ok(None)` if this node has no `reg` or `reg-names` property, or if no region is
+    /// named `name`.
+    pub fn reg_by_name(&self, name: &str) -> Result<Option<(u64, u64)>, DevTreeError> {
+        let index = match self.indexed_resource("reg", "reg-names", name)? {
+            Some((_, index)) => index,
+            None => return Ok(None),
+        };
+        Ok(self
+            .reg()?
+            .nth(index)
+            .map(|entry| (entry.address, entry.size)))
+    }
+
+    /// Returns an iterator which decodes a `<&provider arg0 arg1 ...>` style property, such as
+    /// `gpios`, `clocks`, `pwms` or `interrupts-extended`.
+    ///
+    /// The number of argument cells following each phandle is read from the provider node's
+    /// `cells_prop` property (e.g. `#gpio-cells`). Placeholder entries, whose phandle is `0`, are
+    /// returned without a provider node or arguments. If this node has no `prop` property, the
+    /// returned iterator is empty.
+    pub fn decode_phandle_list<'s>(
+        &self,
+        prop: &str,
+        cells_prop: &'s str,
+    ) -> Result<PhandleArgsIter<'s, 'a, 'i, 'dt>, DevTreeError> {
+        let propbuf = self.try_prop(prop)?.map_or(&[][..], |p| p.raw());
+        Ok(PhandleArgsIter::new(self.index, propbuf, cells_prop))
+    }
+
+    /// Follows a property containing a single phandle (e.g. `interrupt-parent`) to the node it
+    /// references.
+    ///
+    /// Returns `Ok(None)` if this node has no `prop` property, and an [`Err`] if the phandle
+    /// cannot be read or does not reference a node.
+    pub fn follow_phandle(&self, prop: &str) -> Result<Option<Self>, DevTreeError> {
+        match Node::prop(self, prop)? {
+            Some(p) => self
+                .index
+                .node_by_phandle(p.phandle(0)?)
+                .map(Some)
+                .ok_or(DevTreeError::ParseError),
+            None => Ok(None),
+        }
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> Node<'dt> for DevTreeIndexNode<'a, 'i, 'dt> {
+    type NodeProp = DevTreeIndexProp<'a, 'i, 'dt>;
+    type NodePropIter = InfallibleIter<DevTreeIndexNodePropIter<'a, 'i, 'dt>>;
+
+    #[inline]
+    fn name(&self) -> Result<&'dt str, DevTreeError> {
+        DevTreeIndexNode::name(self)
+    }
+
+    #[inline]
+    fn props(&self) -> Self::NodePropIter {
+        InfallibleIter(DevTreeIndexNode::props(self))
+    }
 }
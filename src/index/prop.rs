@@ -1,10 +1,20 @@
+use core::hash::{Hash, Hasher};
 use core::ptr;
 
+#[cfg(any(feature = "alloc", feature = "std"))]
+use alloc::borrow::Cow;
+#[cfg(any(feature = "alloc", feature = "std"))]
+use alloc::string::String;
+#[cfg(any(feature = "alloc", feature = "std"))]
+use alloc::vec::Vec;
+
 use crate::prelude::*;
 
 use crate::base::parse::ParsedProp;
 use crate::base::DevTree;
+use crate::error::DevTreeError;
 
+use super::iters::DevTreeIndexSiblingPropIter;
 use super::tree::{DTINode, DTIProp, DevTreeIndex};
 use super::DevTreeIndexNode;
 
@@ -26,6 +36,16 @@ impl<'a, 'i: 'a, 'dt: 'i> PartialEq for DevTreeIndexProp<'a, 'i, 'dt> {
     }
 }
 
+impl<'a, 'i: 'a, 'dt: 'i> Eq for DevTreeIndexProp<'a, 'i, 'dt> {}
+
+impl<'a, 'i: 'a, 'dt: 'i> Hash for DevTreeIndexProp<'a, 'i, 'dt> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ptr::hash(self.index, state);
+        ptr::hash(self.node, state);
+        ptr::hash(self.prop, state);
+    }
+}
+
 impl<'r, 'a: 'r, 'i: 'a, 'dt: 'i> DevTreeIndexProp<'a, 'i, 'dt> {
     pub(super) fn new(
         index: &'a DevTreeIndex<'i, 'dt>,
@@ -36,6 +56,116 @@ impl<'r, 'a: 'r, 'i: 'a, 'dt: 'i> DevTreeIndexProp<'a, 'i, 'dt> {
     }
 }
 
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexProp<'a, 'i, 'dt> {
+    /// Returns the node named by this property's string value (e.g. `stdout-path`, or the
+    /// properties of `/aliases` and `/__symbols__`).
+    ///
+    /// Values beginning with `/` are resolved as absolute paths, all other values are resolved
+    /// as aliases. Any `:options` suffix (as permitted in `stdout-path`) is ignored.
+    pub fn as_node(&self) -> Result<Option<DevTreeIndexNode<'a, 'i, 'dt>>, DevTreeError> {
+        let value = self.str()?;
+        let path = value.split(':').next().unwrap_or(value);
+        if path.starts_with('/') {
+            Ok(self.index.node_at_path(path))
+        } else {
+            Ok(self.index.alias(path))
+        }
+    }
+
+    /// Returns the index's node which owns this property.
+    pub(super) fn node_ref(&self) -> &'a DTINode<'i, 'dt> {
+        self.node
+    }
+
+    /// Returns an iterator over the other properties of the node which owns this property.
+    ///
+    /// Handy to find a related property (e.g. `reg-names` after finding `reg`) without looking
+    /// up the owning node first.
+    pub fn sibling_props(&self) -> DevTreeIndexSiblingPropIter<'a, 'i, 'dt> {
+        DevTreeIndexSiblingPropIter {
+            iter: self.node().props(),
+            prop: self.clone(),
+        }
+    }
+
+    /// Returns the offset of this property's name within the device tree's strings block.
+    ///
+    /// Properties with the same name offset always have the same name. Producers such as `dtc`
+    /// store each distinct name once, in which case the converse also holds and properties may
+    /// be grouped or compared by name offset without comparing strings. See
+    /// [`DevTreeIndex::nameoff_of`].
+    #[inline]
+    #[must_use]
+    pub fn name_offset(&self) -> usize {
+        self.nameoff()
+    }
+
+    /// Returns the node which owns this property.
+    ///
+    /// This is an alias of [`PropReader::node`].
+    #[inline]
+    pub fn containing_node(&self) -> DevTreeIndexNode<'a, 'i, 'dt> {
+        self.node()
+    }
+
+    /// Returns the name of the node which owns this property.
+    #[inline]
+    pub fn node_name(&self) -> Result<&'dt str, DevTreeError> {
+        self.node().name()
+    }
+
+    /// Returns this property's value decoded as UTF-8, replacing invalid sequences with
+    /// `U+FFFD REPLACEMENT CHARACTER`.
+    ///
+    /// A single trailing NUL terminator is dropped; any other NUL bytes (e.g. those separating
+    /// the strings of a string list) are kept. This is intended for human-facing output such as
+    /// tree dumps. Use [`PropReader::str`] when parsing.
+    ///
+    /// This method is only available with the `alloc` or `std` feature enabled.
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    pub fn str_lossy(&self) -> Cow<'dt, str> {
+        let buf = self.propbuf();
+        let buf = buf.strip_suffix(&[0]).unwrap_or(buf);
+        String::from_utf8_lossy(buf)
+    }
+
+    /// Returns this property's value decoded into an owned vector of native [`u32`] cells.
+    ///
+    /// This is the allocating counterpart of [`PropReader::cells_be`] and fails in the same way
+    /// if the value's length is not a multiple of the cell size. An empty property yields an
+    /// empty vector.
+    ///
+    /// This method is only available with the `alloc` or `std` feature enabled.
+    #[cfg(any(feature = "alloc", feature = "std"))]
+    pub fn cells_vec(&self) -> Result<Vec<u32>, DevTreeError> {
+        Ok(self
+            .cells_be()?
+            .iter()
+            .map(|&cell| u32::from(cell))
+            .collect())
+    }
+
+    /// Returns true if [`PropReader::guess_type`] considers this property's value to be a string
+    /// or a list of strings.
+    #[must_use]
+    pub fn looks_like_string(&self) -> bool {
+        matches!(self.guess_type(), PropType::String | PropType::StringList)
+    }
+
+    /// Returns true if [`PropReader::guess_type`] considers this property's value to be a list
+    /// of cells.
+    #[must_use]
+    pub fn looks_like_cells(&self) -> bool {
+        self.guess_type() == PropType::Cells
+    }
+
+    /// Returns true if this property has no value (e.g. `interrupt-controller;`).
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.guess_type() == PropType::Empty
+    }
+}
+
 impl<'a, 'i: 'a, 'dt: 'i> PropReader<'dt> for DevTreeIndexProp<'a, 'i, 'dt> {
     type NodeType = DevTreeIndexNode<'a, 'i, 'dt>;
 
@@ -55,7 +185,7 @@ impl<'a, 'i: 'a, 'dt: 'i> PropReader<'dt> for DevTreeIndexProp<'a, 'i, 'dt> {
     }
 
     fn node(&self) -> DevTreeIndexNode<'a, 'i, 'dt> {
-        DevTreeIndexNode::new(self.index, self.node)
+        DevTreeIndexNode::new(self.index, self.node_ref())
     }
 }
 
@@ -0,0 +1,186 @@
+//! Relocation of an index's buffer, see [`DevTreeIndex::into_relocatable`].
+
+use core::mem::{align_of, size_of};
+use core::ops::Range;
+use core::ptr::{addr_of, addr_of_mut};
+use core::slice;
+
+use crate::base::DevTree;
+use crate::error::DevTreeError;
+
+use super::tree::{DTINode, DTIProp, DevTreeIndex};
+
+/// A description of an index's memory which allows the index to be restored after its buffer,
+/// the device tree, or both were moved.
+///
+/// The index's memory holds absolute pointers, both between its own nodes and into the device
+/// tree. This records where that memory and the device tree were located when the index was
+/// built, so that [`RelocatableIndex::rehydrate`] may translate every pointer to a new location.
+///
+/// The description is plain data. It may be stashed alongside a copy of the index's memory,
+/// e.g. by a bootloader which builds an index once and hands it on after relocating itself.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RelocatableIndex {
+    base: usize,
+    size: usize,
+    fdt_base: usize,
+    fdt_totalsize: usize,
+}
+
+/// The translation of pointers from an index's original location to its new one.
+pub(super) struct Relocation {
+    old_base: usize,
+    new_base: usize,
+    size: usize,
+    old_fdt_base: usize,
+    new_fdt: *const u8,
+    fdt_len: usize,
+}
+
+impl Relocation {
+    /// Translates a link between nodes, which must lie within the relocated memory.
+    pub(super) fn node<T>(&self, ptr: *const T) -> Result<*const T, DevTreeError> {
+        if ptr.is_null() {
+            return Ok(ptr);
+        }
+        let offset = (ptr as usize).wrapping_sub(self.old_base);
+        if offset >= self.size || offset % align_of::<DTINode>() != 0 {
+            return Err(DevTreeError::InvalidIndex(
+                "Node link is outside of the relocated index",
+            ));
+        }
+        Ok((self.new_base + offset) as *const T)
+    }
+
+    /// Translates a slice of the device tree, which must lie within the relocated device tree.
+    ///
+    /// `buf` refers to the original device tree, which may no longer exist, so it is only
+    /// inspected as a raw slice.
+    pub(super) fn fdt_slice<'dt>(&self, buf: *const [u8]) -> Result<&'dt [u8], DevTreeError> {
+        let offset = (buf as *const u8 as usize).wrapping_sub(self.old_fdt_base);
+        match offset.checked_add(buf.len()) {
+            Some(end) if end <= self.fdt_len => {}
+            _ => {
+                return Err(DevTreeError::InvalidIndex(
+                    "Reference is outside of the relocated device tree",
+                ))
+            }
+        }
+        // Safety: The range was verified to lie within the new device tree's buffer above.
+        unsafe { Ok(slice::from_raw_parts(self.new_fdt.add(offset), buf.len())) }
+    }
+}
+
+impl RelocatableIndex {
+    /// Returns the number of bytes of index memory, which begins with the index's root node.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the range of bytes within `buf` which hold the index, where `buf` is the buffer
+    /// the index was originally built in.
+    ///
+    /// Returns `None` if the index does not lie within `buf`.
+    #[must_use]
+    pub fn range_in(&self, buf: &[u8]) -> Option<Range<usize>> {
+        let start = self.base.checked_sub(buf.as_ptr() as usize)?;
+        let end = start + self.size;
+        if end <= buf.len() {
+            Some(start..end)
+        } else {
+            None
+        }
+    }
+
+    /// Restores an index from a copy of its memory in `buf`, pointing it at `fdt`.
+    ///
+    /// `buf` must begin with a copy of the bytes of the original buffer within
+    /// [`RelocatableIndex::range_in`], and must be aligned as required by
+    /// [`DevTreeIndex::get_layout`]. `fdt` may live at a different address than the device tree
+    /// the index was built from.
+    ///
+    /// Every link is checked to lie within the relocated memory, and every reference into the
+    /// device tree to lie within `fdt`. [`DevTreeError::InvalidIndex`] is returned otherwise.
+    ///
+    /// # Safety
+    ///
+    /// `buf` must hold an unmodified copy of the memory of the index this was created from, and
+    /// `fdt` must be a byte-for-byte copy of the device tree that index was built from.
+    pub unsafe fn rehydrate<'i, 'dt>(
+        &self,
+        buf: &'i mut [u8],
+        fdt: DevTree<'dt>,
+    ) -> Result<DevTreeIndex<'i, 'dt>, DevTreeError> {
+        if buf.len() < self.size {
+            return Err(DevTreeError::NotEnoughMemory);
+        }
+        if buf.as_ptr() as usize % align_of::<DTINode>() != 0 {
+            return Err(DevTreeError::InvalidParameter(
+                "index buffer is not sufficiently aligned",
+            ));
+        }
+        if fdt.totalsize() != self.fdt_totalsize {
+            return Err(DevTreeError::InvalidParameter(
+                "device tree differs from the one indexed",
+            ));
+        }
+
+        let reloc = Relocation {
+            old_base: self.base,
+            new_base: buf.as_mut_ptr() as usize,
+            size: self.size,
+            old_fdt_base: self.fdt_base,
+            new_fdt: fdt.buf().as_ptr(),
+            fdt_len: fdt.buf().len(),
+        };
+
+        // Nodes are laid out in DFS order, each followed by its properties. Until an entry is
+        // relocated its references are stale, so entries are only accessed through raw pointers.
+        let truncated = DevTreeError::InvalidIndex("Index is truncated");
+        let mut offset = 0;
+        while offset < self.size {
+            if offset + size_of::<DTINode>() > self.size {
+                return Err(truncated);
+            }
+            let node = buf.as_mut_ptr().add(offset) as *mut DTINode;
+            DTINode::relocate(node, &reloc)?;
+            offset += size_of::<DTINode>();
+
+            for _ in 0..addr_of!((*node).num_props).read() {
+                if offset + size_of::<DTIProp>() > self.size {
+                    return Err(truncated);
+                }
+                let prop = buf.as_mut_ptr().add(offset) as *mut DTIProp;
+                let propbuf = addr_of_mut!((*prop).propbuf);
+                let relocated = reloc.fdt_slice((propbuf as *const *const [u8]).read())?;
+                propbuf.write(relocated);
+                offset += size_of::<DTIProp>();
+            }
+        }
+
+        Ok(DevTreeIndex::from_root(fdt, buf.as_ptr() as *const DTINode))
+    }
+}
+
+impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
+    /// Returns a description of this index's memory which allows restoring it with
+    /// [`RelocatableIndex::rehydrate`] after its buffer or the device tree were moved.
+    ///
+    /// The index's memory itself is left untouched. To stash it, copy the bytes of the buffer
+    /// passed to [`Self::new`] within [`RelocatableIndex::range_in`].
+    #[must_use]
+    pub fn into_relocatable(self) -> RelocatableIndex {
+        let root: *const DTINode = self.root().node;
+        let last = self.last_node().node;
+        let end = last as *const DTINode as usize
+            + size_of::<DTINode>()
+            + last.num_props * size_of::<DTIProp>();
+        RelocatableIndex {
+            base: root as usize,
+            size: end - root as usize,
+            fdt_base: self.buf().as_ptr() as usize,
+            fdt_totalsize: self.fdt().totalsize(),
+        }
+    }
+}
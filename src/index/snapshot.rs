@@ -0,0 +1,123 @@
+//! Owned snapshots of a device tree which may be serialized with [`serde`].
+//!
+//! This module is only available with the `serde` feature enabled.
+
+use core::mem::size_of;
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use crate::prelude::*;
+
+use crate::error::DevTreeError;
+
+/// The best-guess value of a property, see [`PropReader::guess_type`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PropValue<'dt> {
+    Empty,
+    String(&'dt str),
+    StringList(Vec<&'dt str>),
+    Cells(Vec<u32>),
+    Bytes(&'dt [u8]),
+}
+
+impl<'dt> PropValue<'dt> {
+    fn from_prop<'a, 'i: 'a>(prop: &DevTreeIndexProp<'a, 'i, 'dt>) -> Result<Self, DevTreeError> {
+        Ok(match prop.guess_type() {
+            PropType::Empty => PropValue::Empty,
+            PropType::String => PropValue::String(prop.str()?),
+            PropType::StringList => {
+                let mut list = Vec::new();
+                let mut iter = prop.iter_str();
+                while let Some(s) = iter.next()? {
+                    list.push(s);
+                }
+                PropValue::StringList(list)
+            }
+            PropType::Cells => PropValue::Cells(
+                (0..prop.length() / size_of::<u32>())
+                    .map(|i| prop.u32(i))
+                    .collect::<Result<_, _>>()?,
+            ),
+            PropType::Bytes => PropValue::Bytes(prop.raw()),
+        })
+    }
+}
+
+impl<'dt> Serialize for PropValue<'dt> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            // Empty properties are boolean flags, present means true.
+            PropValue::Empty => serializer.serialize_bool(true),
+            PropValue::String(s) => serializer.serialize_str(s),
+            PropValue::StringList(list) => list.serialize(serializer),
+            PropValue::Cells(cells) => cells.serialize(serializer),
+            PropValue::Bytes(bytes) => serializer.serialize_bytes(bytes),
+        }
+    }
+}
+
+/// An owned copy of a node, its properties, and all of its descendants.
+///
+/// Returned by [`DevTreeIndexNode::snapshot`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeSnapshot<'dt> {
+    pub name: &'dt str,
+    pub props: BTreeMap<&'dt str, PropValue<'dt>>,
+    pub children: Vec<NodeSnapshot<'dt>>,
+}
+
+impl<'dt> Serialize for NodeSnapshot<'dt> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("NodeSnapshot", 3)?;
+        state.serialize_field("name", self.name)?;
+        state.serialize_field("props", &self.props)?;
+        state.serialize_field("children", &self.children)?;
+        state.end()
+    }
+}
+
+impl<'dt> NodeSnapshot<'dt> {
+    /// Snapshots `node` and its properties, leaving its children to the caller.
+    fn shallow<'a, 'i: 'a>(node: &DevTreeIndexNode<'a, 'i, 'dt>) -> Result<Self, DevTreeError> {
+        let mut props = BTreeMap::new();
+        for prop in node.props() {
+            props.insert(prop.name()?, PropValue::from_prop(&prop)?);
+        }
+
+        Ok(NodeSnapshot {
+            name: node.name()?,
+            props,
+            children: Vec::new(),
+        })
+    }
+}
+
+impl<'a, 'i: 'a, 'dt: 'i> DevTreeIndexNode<'a, 'i, 'dt> {
+    /// Returns an owned snapshot of this node and all of its descendants.
+    ///
+    /// The tree is walked with an explicit stack, so deeply nested trees won't overflow the call
+    /// stack.
+    pub fn snapshot(&self) -> Result<NodeSnapshot<'dt>, DevTreeError> {
+        // Each entry is a partially built snapshot along with its yet to be visited children.
+        let mut stack = Vec::new();
+        stack.push((NodeSnapshot::shallow(self)?, self.children()));
+
+        loop {
+            // Unwrap OK, the stack is only emptied when we return below.
+            let (_, children) = stack.last_mut().unwrap();
+            if let Some(child) = children.next() {
+                stack.push((NodeSnapshot::shallow(&child)?, child.children()));
+                continue;
+            }
+
+            let (done, _) = stack.pop().unwrap();
+            match stack.last_mut() {
+                Some((parent, _)) => parent.children.push(done),
+                None => return Ok(done),
+            }
+        }
+    }
+}
@@ -1,19 +1,25 @@
 use core::alloc::Layout;
+use core::fmt;
 use core::marker::PhantomData;
 use core::mem::{align_of, size_of};
-use core::ptr::null_mut;
+use core::ops::ControlFlow;
+use core::ptr::{self, addr_of_mut, null_mut};
 
 use crate::prelude::*;
 
 use super::iters::{
-    DevTreeIndexCompatibleNodeIter, DevTreeIndexIter, DevTreeIndexNodeIter, DevTreeIndexPropIter,
+    DevTreeIndexBfsIter, DevTreeIndexCompatibleNodeIter, DevTreeIndexDepthIter,
+    DevTreeIndexDepthNodeIter, DevTreeIndexDeviceTypeNodeIter, DevTreeIndexEnabledNodeIter,
+    DevTreeIndexIter, DevTreeIndexNodeIter, DevTreeIndexPropIter, DevTreeIndexRegNodeIter,
+    DevTreeIndexUniqueCompatibleNodeIter,
 };
+use super::node::NodeHandle;
+use super::reloc::Relocation;
 use super::DevTreeIndexNode;
-use crate::base::item::DevTreeItem;
-use crate::base::iters::DevTreeIter;
 use crate::base::parse::{DevTreeParseIter, ParsedBeginNode, ParsedProp, ParsedTok};
 use crate::base::DevTree;
 use crate::error::DevTreeError;
+use crate::spec::Phandle;
 
 unsafe fn aligned_ptr_in<T>(buf: &mut [u8], offset: usize) -> Result<*mut T, DevTreeError> {
     // Get the aligned offset
@@ -26,15 +32,49 @@ unsafe fn aligned_ptr_in<T>(buf: &mut [u8], offset: usize) -> Result<*mut T, Dev
     Ok(t_slice_ref.as_mut_ptr() as *mut T)
 }
 
+/// Displays the full path of a node, built by walking its ancestors.
+struct NodePath<'a, 'i: 'a, 'dt: 'i>(Option<DevTreeIndexNode<'a, 'i, 'dt>>);
+
+impl fmt::Display for NodePath<'_, '_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fn write_path(node: &DevTreeIndexNode, f: &mut fmt::Formatter) -> fmt::Result {
+            match node.parent() {
+                Some(parent) => {
+                    write_path(&parent, f)?;
+                    write!(f, "/{}", node.name().unwrap_or("<invalid>"))
+                }
+                // The root's path is written by its children.
+                None => Ok(()),
+            }
+        }
+
+        match &self.0 {
+            Some(node) if node.parent().is_some() => write_path(node, f),
+            _ => f.write_str("/"),
+        }
+    }
+}
+
+/// Returns true if the node `name` matches a component of a path passed to
+/// [`DevTreeIndex::node_at_path`].
+pub(super) fn path_component_matches(name: &str, component: &str) -> bool {
+    name == component || (!component.contains('@') && name.split('@').next() == Some(component))
+}
+
 pub(super) struct DTIProp<'dt> {
     pub propbuf: &'dt [u8],
     pub nameoff: usize,
 }
 
-#[derive(Debug, PartialEq)]
+/// Cloning a [`DevTreeIndex`] is cheap. The clone shares both the device tree buffer and the
+/// index's memory with the original; neither is copied.
+#[derive(Clone, Debug, PartialEq)]
 pub struct DevTreeIndex<'i, 'dt: 'i> {
     fdt: DevTree<'dt>,
     root: *const DTINode<'i, 'dt>,
+    /// The root node's `#address-cells` and `#size-cells`, see [`Self::cache_root_cells`].
+    address_cells: Result<u32, DevTreeError>,
+    size_cells: Result<u32, DevTreeError>,
 }
 
 struct DTIBuilder<'i, 'dt: 'i> {
@@ -61,6 +101,9 @@ pub(super) struct DTINode<'i, 'dt: 'i> {
     // NOTE: We store props like C arrays. Props are a packed array after each node.
     // This is the number of props after this node in memory.
     pub(super) num_props: usize,
+
+    // The offset within the device tree following this node's `FDT_END_NODE` token.
+    pub(super) end: usize,
     _index: PhantomData<&'i u8>,
 }
 
@@ -93,6 +136,40 @@ impl<'i, 'dt: 'i> DTINode<'i, 'dt> {
     pub fn parent(&self) -> Option<&'i DTINode<'i, 'dt>> {
         unsafe { self.parent.as_ref() }
     }
+
+    /// Returns the first node in DFS order which follows this node's subtree.
+    pub fn next_after_subtree(&self) -> Option<&'i DTINode<'i, 'dt>> {
+        let mut cur = self;
+        loop {
+            if let Some(sibling) = cur.next_sibling() {
+                return Some(sibling);
+            }
+            cur = cur.parent()?;
+        }
+    }
+
+    /// Rewrites the links and name of the node at `node` to refer to a relocated index and device
+    /// tree.
+    ///
+    /// # Safety
+    ///
+    /// `node` must be aligned and valid for reads and writes of a `DTINode`. Until this returns
+    /// `Ok`, its links and name may point to memory which no longer exists, so no reference to it
+    /// may be created. Its fields are only accessed through raw pointers here.
+    pub(super) unsafe fn relocate(node: *mut Self, reloc: &Relocation) -> Result<(), DevTreeError> {
+        let parent = addr_of_mut!((*node).parent);
+        parent.write(reloc.node(parent.read())?);
+        let first_child = addr_of_mut!((*node).first_child);
+        first_child.write(reloc.node(first_child.read())?);
+        let next = addr_of_mut!((*node).next);
+        next.write(reloc.node(next.read())?);
+
+        // The stale name is read as a raw slice, as it may no longer be a valid reference.
+        let name = addr_of_mut!((*node).name);
+        let relocated = reloc.fdt_slice((name as *const *const [u8]).read())?;
+        name.write(relocated);
+        Ok(())
+    }
 }
 
 impl<'i, 'dt: 'i> DTIBuilder<'i, 'dt> {
@@ -122,6 +199,8 @@ impl<'i, 'dt: 'i> DTIBuilder<'i, 'dt> {
 
                 name: node.name,
                 num_props: 0,
+                // set once the node's end is parsed
+                end: 0,
                 _index: PhantomData,
             };
 
@@ -187,7 +266,8 @@ impl<'i, 'dt: 'i> DTIBuilder<'i, 'dt> {
         Ok(())
     }
 
-    pub fn parsed_end_node(&mut self) -> Result<(), DevTreeError> {
+    /// Closes the current node, whose `FDT_END_NODE` token is followed by offset `end`.
+    pub fn parsed_end_node(&mut self, end: usize) -> Result<(), DevTreeError> {
         // There were more EndNode tokens than BeginNode ones.
         if self.cur_node.is_null() {
             return Err(DevTreeError::ParseError);
@@ -200,6 +280,7 @@ impl<'i, 'dt: 'i> DTIBuilder<'i, 'dt> {
         //             We're the only thread which has access to the buffer at this time, so this
         //             is thread-safe.
         unsafe {
+            (*self.cur_node).end = end;
             // Change the current node back to the parent.
             self.cur_node = (*self.cur_node).parent as *mut DTINode;
         }
@@ -243,10 +324,25 @@ impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
                 _ => break,
             }
         }
+        log_warn!("Structure block does not begin with a root node");
         Err(DevTreeError::ParseError)
     }
 
     pub fn get_layout(fdt: &'i DevTree<'dt>) -> Result<Layout, DevTreeError> {
+        Self::get_layout_filtered(fdt, |_| true)
+    }
+
+    /// Returns the layout of the buffer required by [`Self::new_filtered`] when called with the
+    /// same `keep` filter.
+    ///
+    /// See [`Self::new_filtered`] for a description of `keep`.
+    pub fn get_layout_filtered<F>(
+        fdt: &'i DevTree<'dt>,
+        mut keep: F,
+    ) -> Result<Layout, DevTreeError>
+    where
+        F: FnMut(&'dt [u8]) -> bool,
+    {
         // Size may require alignment of DTINode.
         let mut size = 0usize;
 
@@ -266,11 +362,20 @@ impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
         // + size_of::<DTINode>
         const_assert_eq!(align_of::<DTINode>(), align_of::<DTIProp>());
 
-        let mut iter = DevTreeIter::new(fdt);
-        while let Some(item) = iter.next()? {
-            match item {
-                DevTreeItem::Node(_) => size += size_of::<DTINode>(),
-                DevTreeItem::Prop(_) => size += size_of::<DTIProp>(),
+        let mut seen_root = false;
+        let mut iter = DevTreeParseIter::new(fdt);
+        while let Some(tok) = iter.next()? {
+            match tok {
+                ParsedTok::BeginNode(node) => {
+                    if seen_root && !keep(node.name) {
+                        iter.skip_current_subtree()?;
+                        continue;
+                    }
+                    seen_root = true;
+                    size += size_of::<DTINode>();
+                }
+                ParsedTok::Prop(_) => size += size_of::<DTIProp>(),
+                ParsedTok::EndNode | ParsedTok::Nop => {}
             }
         }
 
@@ -286,13 +391,31 @@ impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
     }
 
     pub fn new(fdt: DevTree<'dt>, buf: &'i mut [u8]) -> Result<Self, DevTreeError> {
+        Self::new_filtered(fdt, buf, |_| true)
+    }
+
+    /// Builds an index which only contains the nodes accepted by `keep`.
+    ///
+    /// `keep` is called with the raw name of every node other than the root. Nodes it rejects are
+    /// left out of the index along with their entire subtree, reducing the size of the buffer
+    /// required, see [`Self::get_layout_filtered`].
+    pub fn new_filtered<F>(
+        fdt: DevTree<'dt>,
+        buf: &'i mut [u8],
+        mut keep: F,
+    ) -> Result<Self, DevTreeError>
+    where
+        F: FnMut(&'dt [u8]) -> bool,
+    {
         let mut iter = DevTreeParseIter::new(&fdt);
 
         let mut builder = unsafe { Self::init_builder(buf, &mut iter) }?;
 
-        let this = Self {
+        let mut this = Self {
             fdt,
             root: builder.cur_node,
+            address_cells: Ok(2),
+            size_cells: Ok(1),
         };
 
         // The builder should have setup a root node or returned an Err.
@@ -303,31 +426,232 @@ impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
         // Front will be used as a temporary work section to  build the nodes as we parse them.
         // The back will be used to save completely parsed nodes.
         while let Some(item) = iter.next()? {
-            match item {
-                ParsedTok::BeginNode(node) => {
-                    builder.parsed_node(&node)?;
-                }
-                ParsedTok::Prop(prop) => {
-                    builder.parsed_prop(&prop)?;
-                }
-                ParsedTok::EndNode => {
-                    builder.parsed_end_node()?;
+            let res = match item {
+                ParsedTok::BeginNode(node) if !keep(node.name) => {
+                    iter.skip_current_subtree()?;
+                    // As with an EndNode, no properties may follow the skipped node.
+                    builder.in_node_header = false;
+                    continue;
                 }
+                ParsedTok::BeginNode(node) => builder.parsed_node(&node),
+                ParsedTok::Prop(prop) => builder.parsed_prop(&prop),
+                ParsedTok::EndNode => builder.parsed_end_node(iter.offset),
                 ParsedTok::Nop => continue,
+            };
+            if let Err(err) = res {
+                log_warn!(
+                    "Failed to index the token ending at offset {:#x}: {:?}",
+                    iter.offset,
+                    err
+                );
+                return Err(err);
             }
         }
+
+        // Nodes left open by the end of the structure block extend up to its FDT_END token.
+        let end = iter.offset - size_of::<u32>();
+        while !builder.cur_node.is_null() {
+            builder.parsed_end_node(end)?;
+        }
+
+        this.cache_root_cells();
         Ok(this)
     }
 
+    /// Creates an index whose root node is `root`.
+    ///
+    /// # Safety
+    ///
+    /// `root` must point to a fully linked index of `fdt` which lives for `'i`.
+    pub(super) unsafe fn from_root(fdt: DevTree<'dt>, root: *const DTINode<'i, 'dt>) -> Self {
+        let mut this = Self {
+            fdt,
+            root,
+            address_cells: Ok(2),
+            size_cells: Ok(1),
+        };
+        this.cache_root_cells();
+        this
+    }
+
+    // Reads the root node's cell counts once its properties have been indexed. Absent properties
+    // leave the defaults defined by the devicetree specification. Unreadable ones are kept as
+    // errors and returned by the accessors below.
+    fn cache_root_cells(&mut self) {
+        let root = self.root();
+        let address_cells = root.cell_count("#address-cells", 2);
+        let size_cells = root.cell_count("#size-cells", 1);
+        self.address_cells = address_cells;
+        self.size_cells = size_cells;
+    }
+
+    /// Returns the root node's `#address-cells`, which describes the addresses of the root's
+    /// children, or its default of `2` if the root does not define it.
+    ///
+    /// The value is read once when the index is built, so this takes `O(1)` time. If the
+    /// property could not be read, the error encountered is returned instead.
+    pub fn root_address_cells(&self) -> Result<u32, DevTreeError> {
+        self.address_cells
+    }
+
+    /// Returns the root node's `#size-cells`, which describes the sizes of the root's children,
+    /// or its default of `1` if the root does not define it.
+    ///
+    /// The value is read once when the index is built, so this takes `O(1)` time. If the
+    /// property could not be read, the error encountered is returned instead.
+    pub fn root_size_cells(&self) -> Result<u32, DevTreeError> {
+        self.size_cells
+    }
+
     pub fn root(&self) -> DevTreeIndexNode<'_, 'i, 'dt> {
         // Unsafe OK. The root node always exits.
         unsafe { DevTreeIndexNode::new(self, &*self.root) }
     }
 
+    /// Returns the first node of the tree in DFS order. This is always the root node.
+    pub fn first_node(&self) -> DevTreeIndexNode<'_, 'i, 'dt> {
+        self.root()
+    }
+
+    /// Returns the last node of the tree in DFS order.
+    ///
+    /// This follows node links only; no properties are visited.
+    pub fn last_node(&self) -> DevTreeIndexNode<'_, 'i, 'dt> {
+        let mut node = self.root().node;
+        while let Some(next) = node.next_dfs() {
+            node = next;
+        }
+        DevTreeIndexNode::new(self, node)
+    }
+
     pub fn fdt(&self) -> &DevTree<'dt> {
         &self.fdt
     }
 
+    /// Verifies that the internal links of this index are consistent.
+    ///
+    /// This walks every node of the index checking that each child points back to its parent,
+    /// that sibling chains terminate, and that every node's name and properties lie within the
+    /// device tree buffer. It is intended as a defensive check for indices kept in shared or
+    /// potentially corrupted memory.
+    ///
+    /// Returns [`DevTreeError::InvalidIndex`] describing the first inconsistency found.
+    pub fn validate(&self) -> Result<(), DevTreeError> {
+        let buf = self.fdt.buf();
+        let in_buf = |slice: &[u8]| {
+            let (start, end) = (buf.as_ptr() as usize, buf.as_ptr() as usize + buf.len());
+            let offset = slice.as_ptr() as usize;
+            start <= offset && offset + slice.len() <= end
+        };
+
+        // Every node requires at least a begin and end token, so a tree can't contain more
+        // nodes than this. Exceeding it means the links contain a cycle.
+        let max_nodes = self.fdt.buf().len() / (2 * size_of::<u32>());
+
+        let root = self.root().node;
+        if root.parent().is_some() {
+            return Err(DevTreeError::InvalidIndex("Root node has a parent"));
+        }
+
+        let mut count = 0;
+        let mut cur = Some(root);
+        while let Some(node) = cur {
+            count += 1;
+            if count > max_nodes {
+                return Err(DevTreeError::InvalidIndex("Node links contain a cycle"));
+            }
+
+            if !ptr::eq(node, root) && node.parent().is_none() {
+                return Err(DevTreeError::InvalidIndex("Non-root node has no parent"));
+            }
+            if !in_buf(node.name) {
+                return Err(DevTreeError::InvalidIndex(
+                    "Node name is outside of the device tree",
+                ));
+            }
+            let name_offset = node.name.as_ptr() as usize - buf.as_ptr() as usize;
+            if node.end < name_offset || node.end > buf.len() {
+                return Err(DevTreeError::InvalidIndex(
+                    "Node end is outside of the device tree",
+                ));
+            }
+
+            let mut siblings = 0;
+            let mut child = node.first_child();
+            while let Some(c) = child {
+                if !matches!(c.parent(), Some(p) if ptr::eq(p, node)) {
+                    return Err(DevTreeError::InvalidIndex(
+                        "Child node does not point back to its parent",
+                    ));
+                }
+                siblings += 1;
+                if siblings > max_nodes {
+                    return Err(DevTreeError::InvalidIndex(
+                        "Sibling chain does not terminate",
+                    ));
+                }
+                child = c.next_sibling();
+            }
+
+            for idx in 0..node.num_props {
+                // Unsafe OK, the index is bounded by the node's number of props. We only verify
+                // that the props point into the device tree.
+                let prop = unsafe { node.prop_unchecked(idx) };
+                if !in_buf(prop.propbuf) {
+                    return Err(DevTreeError::InvalidIndex(
+                        "Property value is outside of the device tree",
+                    ));
+                }
+                let prop = DevTreeIndexProp::new(self, node, prop);
+                if prop.name().is_err() {
+                    return Err(DevTreeError::InvalidIndex("Property name is unreadable"));
+                }
+            }
+
+            cur = node.next_dfs();
+        }
+        Ok(())
+    }
+
+    /// Returns the first node which shares its full name (including any unit address) with an
+    /// earlier sibling, or `None` if all siblings are uniquely named.
+    ///
+    /// The specification requires sibling nodes to have distinct names. The parent of the
+    /// returned node, and so its path, is available through [`DevTreeIndexNode::parent`].
+    pub fn find_duplicate_sibling(&self) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        for node in self.nodes() {
+            for (i, child) in node.children().enumerate() {
+                if node
+                    .children()
+                    .take(i)
+                    .any(|prev| prev.node.name == child.node.name)
+                {
+                    return Some(child);
+                }
+            }
+        }
+        None
+    }
+
+    /// Verifies that the direct children of every node have distinct names, as required by the
+    /// specification.
+    ///
+    /// Returns [`DevTreeError::DuplicateNodeName`] identifying the first node which shares its
+    /// name with an earlier sibling, see [`Self::find_duplicate_sibling`].
+    pub fn check_unique_sibling_names(&self) -> Result<(), DevTreeError> {
+        match self.find_duplicate_sibling() {
+            Some(dup) => {
+                log_warn!(
+                    "Duplicate node name {} beneath {}",
+                    dup.name().unwrap_or("<invalid>"),
+                    NodePath(dup.parent())
+                );
+                Err(DevTreeError::DuplicateNodeName(dup.to_handle()))
+            }
+            None => Ok(()),
+        }
+    }
+
     #[must_use]
     pub fn nodes(&self) -> DevTreeIndexNodeIter<'_, 'i, 'dt> {
         DevTreeIndexNodeIter(self.items())
@@ -343,6 +667,96 @@ impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
         DevTreeIndexIter::new(self)
     }
 
+    /// Returns an iterator over all items of the index paired with their depth in the tree.
+    ///
+    /// The root node has a depth of `0`. Properties report the depth of the node which contains
+    /// them.
+    #[must_use]
+    pub fn items_with_depth(&self) -> DevTreeIndexDepthIter<'_, 'i, 'dt> {
+        DevTreeIndexDepthIter::new(self)
+    }
+
+    /// Returns an iterator over all nodes at exactly `depth` in DFS order, where the root node
+    /// has a depth of `0`.
+    ///
+    /// Only nodes down to `depth` are walked; deeper subtrees and properties are skipped.
+    #[must_use]
+    pub fn nodes_at_depth(&self, depth: usize) -> DevTreeIndexDepthNodeIter<'_, 'i, 'dt> {
+        DevTreeIndexDepthNodeIter::new(self, depth)
+    }
+
+    /// Returns an iterator over all enabled nodes in DFS order.
+    ///
+    /// Disabled nodes, i.e. those whose `status` is neither absent nor `"okay"`, are skipped
+    /// along with their entire subtree.
+    #[must_use]
+    pub fn enabled_nodes(&self) -> DevTreeIndexEnabledNodeIter<'_, 'i, 'dt> {
+        DevTreeIndexEnabledNodeIter::new(self)
+    }
+
+    /// Returns an iterator over all nodes in breadth-first order.
+    ///
+    /// This requires no allocation, but takes `O(n * h)` time to iterate over the entire tree,
+    /// where `h` is the height of the tree. See [`DevTreeIndexBfsIter`].
+    #[must_use]
+    pub fn nodes_bfs(&self) -> DevTreeIndexBfsIter<'_, 'i, 'dt> {
+        DevTreeIndexBfsIter::new(self)
+    }
+
+    /// Calls `visit` with each node of the index and its depth in DFS order, tracking the current
+    /// path in the caller provided `stack` rather than recursing.
+    ///
+    /// The root node has a depth of `0`, so `stack` must hold at least one more entry than the
+    /// depth of the deepest node. If it is too short, [`DevTreeError::LimitExceeded`] is returned
+    /// once a node deeper than it can hold is reached. Traversal stops early if `visit` returns
+    /// [`ControlFlow::Break`].
+    ///
+    /// This never recurses and requires no allocation, so it suits callers which must bound
+    /// their stack usage. On return, the contents of `stack` are unspecified.
+    pub fn dfs_with_stack<'a, F>(
+        &'a self,
+        stack: &mut [Option<DevTreeIndexNode<'a, 'i, 'dt>>],
+        mut visit: F,
+    ) -> Result<(), DevTreeError>
+    where
+        F: FnMut(&DevTreeIndexNode<'a, 'i, 'dt>, usize) -> ControlFlow<()>,
+    {
+        let root = self.root();
+        let slot = stack.first_mut().ok_or(DevTreeError::LimitExceeded)?;
+        if let ControlFlow::Break(()) = visit(&root, 0) {
+            return Ok(());
+        }
+        *slot = Some(root);
+
+        let mut depth = 0;
+        loop {
+            // Unwrap OK, every entry up to `depth` is populated.
+            let node = stack[depth].as_ref().unwrap().node;
+            let next = if let Some(child) = node.first_child() {
+                depth += 1;
+                child
+            } else {
+                loop {
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                    let node = stack[depth].take().unwrap().node;
+                    match node.next_sibling() {
+                        Some(sibling) => break sibling,
+                        None => depth -= 1,
+                    }
+                }
+            };
+
+            let next = DevTreeIndexNode::new(self, next);
+            let slot = stack.get_mut(depth).ok_or(DevTreeError::LimitExceeded)?;
+            if let ControlFlow::Break(()) = visit(&next, depth) {
+                return Ok(());
+            }
+            *slot = Some(next);
+        }
+    }
+
     pub fn compatible_nodes<'a, 's>(
         &'a self,
         string: &'s str,
@@ -353,6 +767,141 @@ impl<'i, 'dt: 'i> DevTreeIndex<'i, 'dt> {
         }
     }
 
+    /// Calls `f` with each node whose `compatible` property contains `string`.
+    ///
+    /// This is equivalent to iterating over [`Self::compatible_nodes`], which is often simpler
+    /// when registering drivers from a table.
+    pub fn for_each_compatible<'a, F>(&'a self, string: &str, f: F)
+    where
+        F: FnMut(DevTreeIndexNode<'a, 'i, 'dt>),
+    {
+        self.compatible_nodes(string).for_each(f);
+    }
+
+    /// Returns an iterator over all nodes which are compatible with any of the provided strings.
+    ///
+    /// Unlike chaining [`Self::compatible_nodes`] for each string, a node which matches several
+    /// of the strings is only returned once. Nodes are returned in DFS order.
+    pub fn unique_compatible_nodes<'a, 's>(
+        &'a self,
+        strings: &'s [&'s str],
+    ) -> DevTreeIndexUniqueCompatibleNodeIter<'s, 'a, 'i, 'dt> {
+        DevTreeIndexUniqueCompatibleNodeIter {
+            iter: self.nodes(),
+            strings,
+        }
+    }
+
+    /// Returns an iterator over all nodes whose `device_type` property equals `device_type`
+    /// (e.g. `"cpu"` or `"memory"`).
+    pub fn nodes_with_device_type<'a, 's>(
+        &'a self,
+        device_type: &'s str,
+    ) -> DevTreeIndexDeviceTypeNodeIter<'s, 'a, 'i, 'dt> {
+        DevTreeIndexDeviceTypeNodeIter {
+            iter: self.nodes(),
+            device_type,
+        }
+    }
+
+    /// Returns an iterator over all nodes which have a `reg` property, each paired with an
+    /// iterator over the regions it describes.
+    ///
+    /// This is the usual starting point for discovering memory-mapped devices. Nodes whose `reg`
+    /// property cannot be decoded yield an [`Err`] rather than being skipped.
+    #[must_use]
+    pub fn devices_with_reg(&self) -> DevTreeIndexRegNodeIter<'_, 'i, 'dt> {
+        DevTreeIndexRegNodeIter(self.nodes())
+    }
+
+    /// Returns the strings block offset of the name of the first property named `name`, or
+    /// [`None`] if no property has that name.
+    ///
+    /// The returned offset may then be compared against [`DevTreeIndexProp::name_offset`] rather
+    /// than comparing each property's name. This relies on the device tree storing each distinct
+    /// name once, as `dtc` does.
+    pub fn nameoff_of(&self, name: &str) -> Option<usize> {
+        self.props()
+            .find(|p| p.name_bytes() == Ok(name.as_bytes()))
+            .map(|p| p.name_offset())
+    }
+
+    /// Returns the node identified by a [`NodeHandle`] previously obtained from a node of this
+    /// index, or [`None`] if no node of this index matches it.
+    ///
+    /// This searches the index's nodes and takes `O(n)` time.
+    pub fn node_from_handle(&self, handle: NodeHandle) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        self.nodes().find(|node| node.to_handle() == handle)
+    }
+
+    /// Returns the [`DevTreeIndexNode`] whose `phandle` (or legacy `linux,phandle`) property
+    /// matches the provided [`Phandle`].
+    pub fn node_by_phandle(&self, phandle: Phandle) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        self.props()
+            .find(|p| match p.name() {
+                Ok("phandle") | Ok("linux,phandle") => p.phandle(0) == Ok(phandle),
+                _ => false,
+            })
+            .map(|p| p.node())
+    }
+
+    /// Returns the node at the provided absolute path (e.g. `/soc/pci@30000000`).
+    ///
+    /// Path components without a unit address (e.g. `/memory`) match a node of that name
+    /// regardless of its unit address.
+    pub fn node_at_path(&self, path: &str) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        if !path.starts_with('/') {
+            return None;
+        }
+
+        let mut node = self.root();
+        for component in path.split('/').filter(|c| !c.is_empty()) {
+            node = node.children().find(|child| match child.name() {
+                Ok(name) => path_component_matches(name, component),
+                Err(_) => false,
+            })?;
+        }
+        Some(node)
+    }
+
+    /// Returns the node referenced by the provided alias, as defined by the `/aliases` node.
+    pub fn alias(&self, name: &str) -> Option<DevTreeIndexNode<'_, 'i, 'dt>> {
+        let path = self
+            .node_at_path("/aliases")?
+            .props()
+            .find(|p| p.name() == Ok(name))?
+            .str()
+            .ok()?;
+        self.node_at_path(path)
+    }
+
+    /// Like [`Self::node_by_phandle`], but returns [`DevTreeError::NotFound`] if no node has the
+    /// provided [`Phandle`].
+    pub fn node_by_phandle_required(
+        &self,
+        phandle: Phandle,
+    ) -> Result<DevTreeIndexNode<'_, 'i, 'dt>, DevTreeError> {
+        self.node_by_phandle(phandle).ok_or(DevTreeError::NotFound)
+    }
+
+    /// Like [`Self::node_at_path`], but returns [`DevTreeError::NotFound`] if the path does not
+    /// exist.
+    pub fn node_at_path_required(
+        &self,
+        path: &str,
+    ) -> Result<DevTreeIndexNode<'_, 'i, 'dt>, DevTreeError> {
+        self.node_at_path(path).ok_or(DevTreeError::NotFound)
+    }
+
+    /// Like [`Self::alias`], but returns [`DevTreeError::NotFound`] if the alias does not exist
+    /// or does not resolve to a node.
+    pub fn alias_required(
+        &self,
+        name: &str,
+    ) -> Result<DevTreeIndexNode<'_, 'i, 'dt>, DevTreeError> {
+        self.alias(name).ok_or(DevTreeError::NotFound)
+    }
+
     #[must_use]
     pub fn buf(&self) -> &'dt [u8] {
         self.fdt.buf()
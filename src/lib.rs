@@ -18,6 +18,13 @@
 //! default-features = false
 //! ```
 //!
+//! Enabling the optional `serde` feature (which requires an allocator) adds
+//! [`DevTreeIndexNode::snapshot`](index::DevTreeIndexNode::snapshot), an owned copy of a node
+//! and its descendants which implements `serde::Serialize`.
+//!
+//! Enabling the optional `log` feature emits [`log`](https://docs.rs/log) records describing
+//! where a device tree failed to parse or index. Without it, no logging code is compiled in.
+//!
 //! ## Examples
 //!
 //!
@@ -32,9 +39,44 @@ extern crate endian_type_rs as endian_type;
 extern crate memoffset;
 #[macro_use]
 extern crate static_assertions;
+#[cfg(any(feature = "alloc", feature = "std", feature = "serde"))]
+extern crate alloc;
 extern crate fallible_iterator;
+#[cfg(feature = "log")]
+extern crate log;
+#[cfg(feature = "serde")]
+extern crate serde;
 extern crate unsafe_unwrap;
 
+// Logging macros which forward to the `log` crate when the `log` feature is enabled.
+//
+// Without the feature the arguments are still type checked, but never evaluated.
+macro_rules! log_debug {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::debug!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        {
+            if false {
+                let _ = format_args!($($arg)*);
+            }
+        }
+    };
+}
+
+macro_rules! log_warn {
+    ($($arg:tt)*) => {
+        #[cfg(feature = "log")]
+        log::warn!($($arg)*);
+        #[cfg(not(feature = "log"))]
+        {
+            if false {
+                let _ = format_args!($($arg)*);
+            }
+        }
+    };
+}
+
 pub mod base;
 pub mod error;
 pub mod index;
@@ -46,6 +88,25 @@ pub mod common;
 
 pub(crate) mod priv_util;
 
+/// Includes a flattened device tree file as a `&'static [u8]` which is guaranteed to be 4-byte
+/// aligned, as required by [`DevTree`](base::DevTree).
+///
+/// The path is interpreted in the same way as by [`include_bytes!`].
+///
+/// ```
+/// const FDT: &[u8] = fdt_rs::include_fdt!("../tests/riscv64-virt.dtb");
+/// let devtree = unsafe { fdt_rs::base::DevTree::new(FDT) }.unwrap();
+/// ```
+#[macro_export]
+macro_rules! include_fdt {
+    ($path:expr) => {{
+        #[repr(align(4))]
+        struct Aligned<T>(T);
+        const FDT: &[u8] = &Aligned(*include_bytes!($path)).0;
+        FDT
+    }};
+}
+
 // When the doctest feature is enabled, add these utility functions.
 #[cfg(all(any(feature = "doctest", doc), feature = "std"))]
 #[doc(hidden)]
@@ -64,9 +125,7 @@ pub mod doctest {
 
     external_doc_test!(include_str!("../README.md"));
 
-    #[repr(align(4))]
-    struct _Wrapper<T>(T);
-    pub const FDT: &[u8] = &_Wrapper(*include_bytes!("../tests/riscv64-virt.dtb")).0;
+    pub const FDT: &[u8] = include_fdt!("../tests/riscv64-virt.dtb");
 
     pub fn doctest_index<'i, 'dt: 'i>() -> (DevTreeIndex<'i, 'dt>, Vec<u8>) {
         // Create the device tree parser
@@ -1,7 +1,10 @@
-//! Module exporting traits of this library.
+//! Module exporting traits and commonly used types of this library.
 pub(crate) use crate::common::item::UnwrappableDevTreeItem;
 pub(crate) use crate::priv_util::SliceRead;
 
-pub use crate::common::prop::PropReader;
+pub use crate::common::node::Node;
+pub use crate::common::prop::{props_value_eq, PropReader, PropType};
+
+pub use crate::index::{DevTreeIndex, DevTreeIndexItem, DevTreeIndexNode, DevTreeIndexProp};
 
 pub use fallible_iterator::FallibleIterator;
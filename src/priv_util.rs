@@ -15,7 +15,6 @@ pub(crate) trait SliceRead<'a> {
     fn read_be_u32(&self, pos: usize) -> SliceReadResult<u32>;
     fn read_be_u64(&self, pos: usize) -> SliceReadResult<u64>;
     fn read_bstring0(&self, pos: usize) -> SliceReadResult<&'a [u8]>;
-    fn nread_bstring0(&self, pos: usize, len: usize) -> SliceReadResult<&'a [u8]>;
 }
 
 macro_rules! unchecked_be_read {
@@ -69,17 +68,4 @@ impl<'a> SliceRead<'a> for &'a [u8] {
         }
         Err(SliceReadError::UnexpectedEndOfInput)
     }
-
-    fn nread_bstring0(&self, pos: usize, len: usize) -> SliceReadResult<&'a [u8]> {
-        let end = core::cmp::min(len + pos, self.len());
-        for i in pos..end {
-            // Unsafe okay, we just confirmed the length in the let above.
-            unsafe {
-                if *self.get_unchecked(i) == 0 {
-                    return Ok(&self[pos..i]);
-                }
-            }
-        }
-        Err(SliceReadError::UnexpectedEndOfInput)
-    }
 }
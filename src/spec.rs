@@ -2,14 +2,85 @@
 use endian_type::types::{u32_be, u64_be};
 use num_derive::FromPrimitive;
 
+use crate::error::{DevTreeError, Result};
+
 /// Magic number used to denote the beginning of a device tree (as a native machine number).
 pub const FDT_MAGIC: u32 = 0xd00d_feed;
-/// Maximum length of a device tree node name (including null byte)
+/// Maximum length of a device tree node name (including null byte), as recommended by the
+/// specification.
+///
+/// This limit is not enforced while parsing, names including a long unit address may exceed it.
 pub const MAX_NODE_NAME_LEN: usize = 31;
 
 /// Definition of the parsed phandle as a native machine number
 pub type Phandle = u32;
 
+/// The values of a node's `status` property defined by the specification.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeStatus {
+    /// `"okay"`, the device is operational.
+    Okay,
+    /// `"disabled"`, the device is not presently operational.
+    Disabled,
+    /// `"reserved"`, the device is operational but should not be used (e.g. it is controlled by
+    /// other software).
+    Reserved,
+    /// `"fail"`, the device is not operational due to a serious error.
+    Fail,
+}
+
+impl NodeStatus {
+    /// Returns the string value of the `status` property denoting this status.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            NodeStatus::Okay => "okay",
+            NodeStatus::Disabled => "disabled",
+            NodeStatus::Reserved => "reserved",
+            NodeStatus::Fail => "fail",
+        }
+    }
+
+    /// Parses the string value of a `status` property.
+    ///
+    /// The legacy value `"ok"` is accepted as [`NodeStatus::Okay`], and `"fail-sss"`, where `sss`
+    /// describes the detected error, as [`NodeStatus::Fail`]. Returns `None` for any other value.
+    #[must_use]
+    pub fn from_value(value: &str) -> Option<Self> {
+        match value {
+            "okay" | "ok" => Some(NodeStatus::Okay),
+            "disabled" => Some(NodeStatus::Disabled),
+            "reserved" => Some(NodeStatus::Reserved),
+            "fail" => Some(NodeStatus::Fail),
+            _ if value.starts_with("fail-") => Some(NodeStatus::Fail),
+            _ => None,
+        }
+    }
+
+    /// Overwrites an existing `status` property value with this status, padding any remaining
+    /// bytes with NULs.
+    ///
+    /// `value` is usually found with
+    /// [`DevTreeIndexNode::status_value_range`](crate::index::DevTreeIndexNode::status_value_range).
+    /// The value's length is fixed, so if this status and its NUL terminator do not fit an
+    /// [`Err`] containing [`DevTreeError::InvalidParameter`] is returned and `value` is left
+    /// untouched. Growing a property requires rewriting the device tree.
+    pub fn write_value(self, value: &mut [u8]) -> Result<()> {
+        let status = self.as_str().as_bytes();
+        if status.len() >= value.len() {
+            return Err(DevTreeError::InvalidParameter(
+                "status does not fit the existing property",
+            ));
+        }
+        let (head, tail) = value.split_at_mut(status.len());
+        head.copy_from_slice(status);
+        for b in tail {
+            *b = 0;
+        }
+        Ok(())
+    }
+}
+
 /// An enumeration of the tokens used to separate sections within the `dt_struct` section of the FDT.
 #[derive(FromPrimitive)]
 pub enum FdtTok {
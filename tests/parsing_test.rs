@@ -1,8 +1,8 @@
 extern crate fdt_rs;
 
-use fdt_rs::base::DevTree;
+use fdt_rs::base::{DevTree, DevTreeItem};
 use fdt_rs::error::{DevTreeError, Result};
-use fdt_rs::index::DevTreeIndex;
+use fdt_rs::index::{DevTreeIndex, DevTreeIndexItem};
 use fdt_rs::prelude::*;
 
 /// Fallible Basic Iterator
@@ -21,9 +21,7 @@ where
     }
 }
 
-#[repr(align(4))]
-struct _Wrapper<T>(T);
-pub const FDT: &[u8] = &_Wrapper(*include_bytes!("../tests/riscv64-virt.dtb")).0;
+pub const FDT: &[u8] = fdt_rs::include_fdt!("../tests/riscv64-virt.dtb");
 static DFS_NODES: &[&str] = &[
     "", // Root
     "flash@20000000",
@@ -54,6 +52,77 @@ static DFS_NODES: &[&str] = &[
     "clint@2000000",
 ];
 
+/// A u32 aligned, mutable copy of [`FDT`] which may be followed by trailing bytes.
+struct AlignedFdt {
+    buf: Vec<u32>,
+    len: usize,
+}
+
+impl AlignedFdt {
+    fn new(extra: usize) -> Self {
+        let len = FDT.len() + extra;
+        let mut fdt = Self {
+            buf: vec![0u32; len.div_ceil(4)],
+            len,
+        };
+        fdt.bytes_mut()[..FDT.len()].copy_from_slice(FDT);
+        fdt
+    }
+
+    /// Builds a minimal device tree whose root node has a single child named `child`.
+    fn with_child(child: &str) -> Self {
+        fn push_be(buf: &mut Vec<u8>, val: u32) {
+            buf.extend_from_slice(&val.to_be_bytes());
+        }
+
+        let mut dt_struct = Vec::new();
+        push_be(&mut dt_struct, 1);
+        push_be(&mut dt_struct, 0);
+        push_be(&mut dt_struct, 1);
+        dt_struct.extend_from_slice(child.as_bytes());
+        dt_struct.resize((dt_struct.len() + 4) & !3, 0);
+        for tok in &[2, 2, 9] {
+            push_be(&mut dt_struct, *tok);
+        }
+
+        // Header, an empty memory reservation block, the structure block and no strings.
+        let off_dt_struct = 40 + 16;
+        let len = off_dt_struct + dt_struct.len();
+        let mut bytes = Vec::new();
+        for field in &[
+            0xd00d_feed,
+            len as u32,
+            off_dt_struct as u32,
+            len as u32,
+            40,
+            17,
+            16,
+            0,
+            0,
+            dt_struct.len() as u32,
+        ] {
+            push_be(&mut bytes, *field);
+        }
+        bytes.resize(off_dt_struct, 0);
+        bytes.extend_from_slice(&dt_struct);
+
+        let mut fdt = Self {
+            buf: vec![0u32; len / 4],
+            len,
+        };
+        fdt.bytes_mut().copy_from_slice(&bytes);
+        fdt
+    }
+
+    fn bytes(&self) -> &[u8] {
+        unsafe { core::slice::from_raw_parts(self.buf.as_ptr() as *const u8, self.len) }
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        unsafe { core::slice::from_raw_parts_mut(self.buf.as_mut_ptr() as *mut u8, self.len) }
+    }
+}
+
 pub struct FdtIndex<'dt> {
     index: DevTreeIndex<'dt, 'dt>,
     _vec: Vec<u8>,
@@ -72,6 +141,26 @@ fn get_fdt_index<'dt>() -> FdtIndex<'dt> {
     }
 }
 
+/// Builds an index of a copy of the device tree, such as one modified by a test.
+fn index_of(fdt: &AlignedFdt) -> FdtIndex<'_> {
+    filtered_index_of(fdt, |_| true)
+}
+
+/// Builds an index of `fdt` which only includes the nodes accepted by `keep`, see
+/// [`DevTreeIndex::new_filtered`].
+fn filtered_index_of<'dt>(fdt: &'dt AlignedFdt, keep: fn(&[u8]) -> bool) -> FdtIndex<'dt> {
+    unsafe {
+        let devtree = DevTree::new(fdt.bytes()).unwrap();
+        let layout = DevTreeIndex::get_layout_filtered(&devtree, keep).unwrap();
+        let mut vec = vec![0u8; layout.size() + layout.align()];
+        let slice = core::slice::from_raw_parts_mut(vec.as_mut_ptr(), vec.len());
+        FdtIndex {
+            index: DevTreeIndex::new_filtered(devtree, slice, keep).unwrap(),
+            _vec: vec,
+        }
+    }
+}
+
 #[test]
 fn test_readsize_advice() {
     unsafe {
@@ -82,280 +171,2197 @@ fn test_readsize_advice() {
 }
 
 #[test]
-fn reserved_entries_iter() {
+fn is_fdt() {
+    assert!(DevTree::is_fdt(FDT));
+    assert!(DevTree::is_fdt(&FDT[..4]));
+    assert!(!DevTree::is_fdt(&FDT[..3]));
+    assert!(!DevTree::is_fdt(&FDT[1..]));
+
+    // No alignment is required.
+    let mut buf = [0u8; 5];
+    buf[1..].copy_from_slice(&FDT[..4]);
+    assert!(DevTree::is_fdt(&buf[1..]));
+}
+
+#[test]
+fn long_node_name() {
+    let name = "a-long-node-name@0123456789abcdef01234567";
+    assert_eq!(name.len(), 41);
+    let fdt = AlignedFdt::with_child(name);
     unsafe {
-        let blob = DevTree::new(FDT).unwrap();
-        assert!(blob.reserved_entries().count() == 0);
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        blob.check_layout().unwrap();
+        let mut nodes = blob.nodes();
+        assert_eq!(nodes.next().unwrap().unwrap().name(), Ok(""));
+        assert_eq!(nodes.next().unwrap().unwrap().name(), Ok(name));
+        assert!(nodes.next().unwrap().is_none());
     }
 }
 
 #[test]
-fn nodes_iter() {
+fn short_buffer_is_rejected() {
+    let err = DevTreeError::InvalidParameter("buffer smaller than header");
     unsafe {
-        let blob = DevTree::new(FDT).unwrap();
-        let iter = blob.nodes();
-        let mut pair_iter = iter.clone().zip(Fbi(DFS_NODES.iter()));
-        while let Some((node, expected)) = pair_iter.next().unwrap() {
-            assert_eq!(node.name().unwrap(), *expected);
-        }
-        assert!(iter.count().unwrap() == DFS_NODES.len());
+        assert_eq!(DevTree::new(&FDT[..0]), Err(err));
+        assert_eq!(DevTree::read_totalsize(&FDT[..8]), Err(err));
+        assert_eq!(DevTree::new(&FDT[..DevTree::MIN_HEADER_SIZE - 4]), Err(err));
     }
 }
 
 #[test]
-fn nodes_iter_from_raw_pointer() {
+fn pre_v16_is_rejected() {
+    let mut fdt = AlignedFdt::new(0);
+    fdt.bytes_mut()[20..24].copy_from_slice(&15u32.to_be_bytes());
     unsafe {
-        let blob = DevTree::from_raw_pointer(&FDT[0] as *const u8).unwrap();
-        let iter = blob.nodes();
-        let mut pair_iter = iter.clone().zip(Fbi(DFS_NODES.iter()));
-        while let Some((node, expected)) = pair_iter.next().unwrap() {
-            assert_eq!(node.name().unwrap(), *expected);
-        }
-        assert!(iter.count().unwrap() == DFS_NODES.len());
+        assert_eq!(
+            DevTree::new(fdt.bytes()),
+            Err(DevTreeError::UnsupportedVersion(15))
+        );
+    }
+    fdt.bytes_mut()[20..24].copy_from_slice(&16u32.to_be_bytes());
+    unsafe {
+        assert!(DevTree::new(fdt.bytes()).is_ok());
     }
 }
 
-// Test that comparision of props works as expected.
 #[test]
-fn verify_prop_comparisions() {
-    unsafe {
-        let blob = DevTree::new(FDT).unwrap();
+fn include_fdt_is_aligned() {
+    assert_eq!(FDT.as_ptr() as usize % 4, 0);
+    assert_eq!(FDT, &include_bytes!("riscv64-virt.dtb")[..]);
+}
 
-        let props_iter_1 = blob.props();
-        let props_iter_2 = blob.props();
+#[cfg(feature = "std")]
+#[test]
+fn io_error_conversion() {
+    use std::io;
 
-        let mut pair_iter = props_iter_1.zip(props_iter_2);
-        while let Some((prop_1, prop_2)) = pair_iter.next().unwrap() {
-            assert!(prop_1 == prop_2);
-        }
+    fn parse(buf: &[u8]) -> io::Result<DevTree<'_>> {
+        Ok(unsafe { DevTree::new(buf)? })
+    }
 
-        let mut props_iter_1 = blob.props();
-        let props_iter_2 = blob.props();
+    let mut fdt = AlignedFdt::new(0);
+    fdt.bytes_mut()[0] = 0;
+    let err = parse(fdt.bytes()).err().unwrap();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
 
-        // Mess up the lock step iteration, every prop should be different
-        let _ = props_iter_1.next().unwrap();
+    let err = io::Error::from(DevTreeError::NotEnoughMemory);
+    assert_eq!(err.kind(), io::ErrorKind::OutOfMemory);
+    assert_eq!(
+        err.get_ref().unwrap().downcast_ref::<DevTreeError>(),
+        Some(&DevTreeError::NotEnoughMemory)
+    );
+}
 
-        let mut pair_iter = props_iter_1.zip(props_iter_2);
-        while let Some((prop_1, prop_2)) = pair_iter.next().unwrap() {
-            assert!(prop_1 != prop_2);
-        }
+#[test]
+fn new_in_larger_buffer() {
+    let fdt = AlignedFdt::new(64);
+    unsafe {
+        assert_eq!(DevTree::new(fdt.bytes()), Err(DevTreeError::ParseError));
+
+        let blob = DevTree::new_in_larger_buffer(fdt.bytes()).unwrap();
+        assert_eq!(blob.buf().len(), FDT.len());
+        assert_eq!(blob.nodes().count().unwrap(), DFS_NODES.len());
+
+        DevTree::new_in_larger_buffer(&fdt.bytes()[..FDT.len() - 4])
+            .expect_err("Expected failure.");
     }
 }
 
-// Test that comparision of props works as expected.
 #[test]
-fn get_prop_node() {
+fn prop_name_offset_outside_strings_block() {
+    let mut fdt = AlignedFdt::new(0);
+    let (off_dt_struct, size_dt_strings) = unsafe {
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        (blob.off_dt_struct(), blob.size_dt_strings())
+    };
+
+    // The root node has an empty name, so its first prop's nameoff follows the begin node token,
+    // the padded name, the prop token, and the prop length.
+    let nameoff = off_dt_struct + 16;
+    fdt.bytes_mut()[nameoff..nameoff + 4].copy_from_slice(&size_dt_strings.to_be_bytes());
+
     unsafe {
-        let blob = DevTree::new(FDT).unwrap();
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        assert!(blob.props().next() == Err(DevTreeError::ParseError));
 
-        let prop = blob.props().next().unwrap().unwrap();
-        let node = prop.node();
+        let layout = DevTreeIndex::get_layout(&blob);
+        assert_eq!(layout, Err(DevTreeError::ParseError));
+    }
+}
 
-        assert_eq!(node.name().unwrap(), "");
+#[test]
+fn prop_name_terminator_outside_strings_block() {
+    let mut fdt = AlignedFdt::new(0);
+    let size_dt_strings = unsafe { DevTree::new(fdt.bytes()).unwrap().size_dt_strings() };
+
+    // Cut the strings block short so that the last name's NUL terminator falls outside it.
+    fdt.bytes_mut()[32..36].copy_from_slice(&(size_dt_strings - 1).to_be_bytes());
+
+    unsafe {
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        let mut props = blob.props();
+        let err = loop {
+            match props.next() {
+                Ok(Some(_)) => continue,
+                Ok(None) => panic!("expected a parse error"),
+                Err(e) => break e,
+            }
+        };
+        assert_eq!(err, DevTreeError::ParseError);
     }
 }
 
-// Test that comparision of props works as expected.
 #[test]
-fn get_memory_prop_node() {
+fn from_raw_pointer_checks_alignment() {
     unsafe {
-        let blob = DevTree::new(FDT).unwrap();
+        let blob = DevTree::from_raw_pointer(FDT.as_ptr()).unwrap();
+        assert_eq!(blob.totalsize(), FDT.len());
 
-        let mem_prop = blob
-            .props()
-            .find(|p| Ok(p.name()? == "device_type" && p.str()? == "memory"))
-            .unwrap()
-            .expect("Unable to find memory node.");
-        let mem_node = mem_prop.node();
+        let addr = FDT.as_ptr().add(1);
+        let err = DevTreeError::AlignmentError {
+            required: 4,
+            addr: addr as usize,
+        };
+        assert_eq!(DevTree::from_raw_pointer(addr).err(), Some(err));
+        assert_eq!(DevTree::read_totalsize(&FDT[1..]).err(), Some(err));
 
-        let _ = mem_node
-            .props()
-            .find(|p| Ok(p.name()? == "reg"))
-            .unwrap()
-            .expect("Device tree memory node missing 'reg' prop.");
+        // A misaligned structure block is reported at its address within the buffer.
+        let mut fdt = AlignedFdt::new(0);
+        let off_dt_struct = DevTree::new(FDT).unwrap().off_dt_struct() as u32;
+        fdt.bytes_mut()[8..12].copy_from_slice(&(off_dt_struct + 2).to_be_bytes());
+        assert_eq!(
+            DevTree::new(fdt.bytes()).err(),
+            Some(DevTreeError::AlignmentError {
+                required: 4,
+                addr: fdt.bytes().as_ptr() as usize + off_dt_struct as usize + 2,
+            })
+        );
     }
 }
 
-// Test that comparision of nodes works as expected.
 #[test]
-fn verify_node_comparisions() {
+fn nodes_max_depth() {
     unsafe {
         let blob = DevTree::new(FDT).unwrap();
+        let names = |max| {
+            let mut names = Vec::new();
+            let mut iter = blob.nodes_max_depth(max);
+            while let Some(node) = iter.next().unwrap() {
+                names.push(node.name().unwrap());
+            }
+            names
+        };
 
-        let nodes_iter_1 = blob.nodes();
-        let nodes_iter_2 = blob.nodes();
+        assert!(names(-1).is_empty());
+        assert_eq!(names(0), [""]);
 
-        let mut pair_iter = nodes_iter_1.zip(nodes_iter_2);
-        while let Some((node_1, node_2)) = pair_iter.next().unwrap() {
-            assert!(node_1 == node_2);
-        }
+        let shallow = names(1);
+        assert_eq!(shallow.len(), 19);
+        assert_eq!(shallow[1], "flash@20000000");
+        assert_eq!(shallow[18], "soc");
 
-        let mut nodes_iter_1 = blob.nodes();
-        let nodes_iter_2 = blob.nodes();
+        // Nodes following a pruned subtree are still found.
+        let two = names(2);
+        let cpus = two.iter().position(|&n| n == "cpus").unwrap();
+        assert_eq!(
+            two[cpus + 1..cpus + 4],
+            ["cpu-map", "cpu@0", "memory@80000000"]
+        );
 
-        // Mess up the lock step iteration, every node should be different
-        let _ = nodes_iter_1.next().unwrap();
+        assert_eq!(names(isize::MAX).len(), blob.nodes().count().unwrap());
+    }
+}
 
-        let mut pair_iter = nodes_iter_1.zip(nodes_iter_2);
-        while let Some((node_1, node_2)) = pair_iter.next().unwrap() {
-            assert!(node_1 != node_2);
-        }
+#[test]
+fn check_root() {
+    let mut fdt = AlignedFdt::new(0);
+    let off_dt_struct = unsafe {
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        assert_eq!(blob.check_root(), Ok(()));
+        blob.off_dt_struct()
+    };
+
+    // Name the root node "x". The name still fits within the root's padded name field.
+    fdt.bytes_mut()[off_dt_struct + 4] = b'x';
+
+    unsafe {
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        assert_eq!(blob.check_root(), Err(DevTreeError::NamedRootNode));
     }
 }
 
 #[test]
-fn node_prop_iter() {
+fn check_layout() {
+    use fdt_rs::error::FdtBlock;
+
     unsafe {
         let blob = DevTree::new(FDT).unwrap();
-        let mut node_iter = blob.nodes();
-        while let Some(node) = node_iter.next().unwrap() {
-            let mut prop_iter = node.props();
-            while let Some(prop) = prop_iter.next().unwrap() {
-                if prop.length() > 0 {
-                    if let Ok(i) = prop.iter_str().count() {
-                        if i == 0 {
-                            continue;
-                        }
-                        assert!(i < 64);
+        assert_eq!(blob.check_layout(), Ok(()));
+    }
 
-                        let mut iter = prop.iter_str();
-                        while let Some(s) = iter.next().unwrap() {
-                            let _ = s;
-                        }
-                    }
-                }
-            }
-        }
+    // Overwrite a big-endian header field of a fresh copy of the tree and check its layout.
+    let check = |offset: usize, value: u32| {
+        let mut fdt = AlignedFdt::new(0);
+        fdt.bytes_mut()[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+        unsafe { DevTree::new(fdt.bytes()).unwrap().check_layout() }
+    };
+    let (off_dt_struct, off_dt_strings) = unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        (blob.off_dt_struct() as u32, blob.off_dt_strings() as u32)
+    };
+
+    // size_dt_strings
+    assert_eq!(
+        check(32, FDT.len() as u32),
+        Err(DevTreeError::InvalidLayout(FdtBlock::Strings))
+    );
+    // size_dt_struct, which now runs into the strings block.
+    assert_eq!(
+        check(36, off_dt_strings - off_dt_struct + 4),
+        Err(DevTreeError::InvalidLayout(FdtBlock::Strings))
+    );
+    assert_eq!(
+        check(36, FDT.len() as u32),
+        Err(DevTreeError::InvalidLayout(FdtBlock::Struct))
+    );
+    // off_mem_rsvmap
+    assert_eq!(
+        check(16, off_dt_struct),
+        Err(DevTreeError::InvalidLayout(FdtBlock::MemReserve))
+    );
+    assert_eq!(
+        check(16, 4),
+        Err(DevTreeError::InvalidLayout(FdtBlock::MemReserve))
+    );
+}
+
+#[test]
+fn verify_digest() {
+    use fdt_rs::base::DigestAlgo;
+
+    let mut fdt = AlignedFdt::new(0);
+    let (crc, fnv) = unsafe {
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        assert!(blob.bytes_for_digest() == FDT);
+        let crc = 0x1f6e_c95au32.to_be_bytes();
+        let fnv = 0xd012_0c77_3e63_c568u64.to_be_bytes();
+        assert_eq!(blob.verify_digest(&crc, DigestAlgo::Crc32), Ok(true));
+        assert_eq!(blob.verify_digest(&fnv, DigestAlgo::Fnv1a64), Ok(true));
+        assert!(blob.verify_digest(&fnv, DigestAlgo::Crc32).is_err());
+        (crc, fnv)
+    };
+
+    let last = fdt.bytes().len() - 1;
+    fdt.bytes_mut()[last] ^= 1;
+    unsafe {
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        assert_eq!(blob.verify_digest(&crc, DigestAlgo::Crc32), Ok(false));
+        assert_eq!(blob.verify_digest(&fnv, DigestAlgo::Fnv1a64), Ok(false));
     }
 }
 
 #[test]
-fn next_compatible_finds_initial_node() {
+fn prop_length_past_end_of_buffer() {
+    let mut fdt = AlignedFdt::new(0);
+    let off_dt_struct = unsafe { DevTree::new(fdt.bytes()).unwrap().off_dt_struct() };
+
+    // Overwrite the root node's first prop length with a huge value.
+    let len = off_dt_struct + 12;
+    fdt.bytes_mut()[len..len + 4].copy_from_slice(&u32::MAX.to_be_bytes());
+
     unsafe {
-        let fdt = DevTree::new(FDT).unwrap();
-        let node = fdt
-            .compatible_nodes("riscv-virtio")
-            .next()
-            .unwrap()
-            .unwrap();
-        assert!(node.name().unwrap() == ""); // Root node has no "name"
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        assert!(blob.props().next() == Err(DevTreeError::ParseError));
     }
 }
 
 #[test]
-fn next_compatible_finds_final_node() {
+fn reserved_entries_iter() {
     unsafe {
-        let fdt = DevTree::new(FDT).unwrap();
-        let node = fdt
-            .compatible_nodes("riscv,clint0")
-            .next()
-            .unwrap()
-            .unwrap();
-        assert!(node.name().unwrap() == "clint@2000000");
+        let blob = DevTree::new(FDT).unwrap();
+        assert!(blob.reserved_entries().count() == 0);
     }
 }
 
 #[test]
-fn find_all_compatible() {
+fn reserved_entries_iter_stops_at_struct_block() {
+    let mut fdt = AlignedFdt::new(0);
+    let off_mem_rsvmap = unsafe { DevTree::new(fdt.bytes()).unwrap().off_mem_rsvmap() };
+
+    // Overwrite the terminating entry, the block now runs straight into the struct block.
+    fdt.bytes_mut()[off_mem_rsvmap..off_mem_rsvmap + 16].copy_from_slice(&[0xff; 16]);
+
     unsafe {
-        let devtree = DevTree::new(FDT).unwrap();
-        let compat = "virtio,mmio";
-        let exp = "virtio_mmio@1000";
-        let mut count = 0;
-        let exp_count = 8;
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        assert_eq!(blob.reserved_entries().count(), 1);
 
-        if let Some(mut cur) = devtree.root().unwrap() {
-            while let Some(node) = cur.find_next_compatible_node(compat).unwrap() {
-                count += 1;
-                // Verify the prefix matches.
-                // (ascii doesn't have startswith)
-                assert!(node.name().unwrap()[0..exp.len()] == *exp);
-                cur = node;
-                assert!(count <= exp_count);
-            }
-        }
-        assert!(count == exp_count);
+        let mut regions = blob.reserved_regions_bounded(1);
+        assert_eq!(regions.next(), Ok(Some((u64::MAX, u64::MAX))));
+        assert_eq!(regions.next(), Ok(None));
+        assert_eq!(
+            blob.reserved_regions_bounded(0).next(),
+            Err(DevTreeError::LimitExceeded)
+        );
     }
 }
 
-pub mod index_tests {
-    use super::*;
+#[test]
+fn summary() {
+    use fdt_rs::base::DevTreeSummary;
 
-    // Test that we can create an index from a valid device tree
-    #[test]
-    fn create_index() {
-        let _ = get_fdt_index();
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        assert_eq!(
+            blob.summary(),
+            DevTreeSummary {
+                magic: 0xd00d_feed,
+                version: 17,
+                last_comp_version: 2,
+                boot_cpuid_phys: 0,
+                totalsize: FDT.len(),
+                num_reserved: 0,
+            }
+        );
     }
+}
 
-    // Test that our index get_layout returns a usable layout size.
-    #[test]
-    fn create_sized_index() {
-        unsafe {
-            let devtree = DevTree::new(FDT).unwrap();
-            let layout = DevTreeIndex::get_layout(&devtree).unwrap();
-            let mut vec = vec![0u8; layout.size() + layout.align()];
-            DevTreeIndex::new(devtree, vec.as_mut_slice()).unwrap();
-        }
+#[test]
+fn reserved_regions_bounded() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        assert_eq!(blob.reserved_regions_bounded(0).next(), Ok(None));
+        assert_eq!(blob.reserved_regions_bounded(4).count(), Ok(0));
     }
+}
 
-    // Test that an invalid buffer size results in NotEnoughMemory on index allocation.
-    #[test]
-    fn expect_create_index_layout_fails_with_invalid_layout() {
-        unsafe {
-            let devtree = DevTree::new(FDT).unwrap();
-            let layout = DevTreeIndex::get_layout(&devtree).unwrap();
-            let mut vec = vec![0u8; layout.size() - 1];
-            DevTreeIndex::new(devtree, vec.as_mut_slice()).expect_err("Expected failure.");
-        }
-    }
+#[cfg(feature = "std")]
+#[test]
+fn owned_devtree() {
+    use fdt_rs::base::OwnedDevTree;
 
-    // Test DFS iteration using a DevTreeIndex.
-    #[test]
-    fn dfs_iteration() {
-        let idx = get_fdt_index();
-        test_index_dfs(&idx);
-    }
+    let owned = OwnedDevTree::new(FDT.to_vec().into_boxed_slice()).unwrap();
+    assert_eq!(owned.as_bytes(), FDT);
+    assert_eq!(owned.devtree().nodes().count(), Ok(DFS_NODES.len()));
 
-    // Test that iteration over children works as expected.
-    #[test]
-    fn verify_root_children_iteration() {
-        let idx = get_fdt_index();
-        let root = idx.index.root();
-        assert_eq!(root.children().count(), 18);
-    }
+    let mut bad = FDT.to_vec();
+    bad[0] = 0;
+    assert_eq!(
+        OwnedDevTree::new(bad.into_boxed_slice()).err(),
+        Some(DevTreeError::InvalidMagicNumber)
+    );
+}
 
-    // Test that comparision of nodes works as expected.
-    #[test]
-    fn verify_root_children_comparisions() {
-        let idx = get_fdt_index();
-        let root = idx.index.root();
+#[cfg(feature = "std")]
+#[test]
+fn owned_devtree_misaligned() {
+    use fdt_rs::base::OwnedDevTree;
 
-        let mut prev = None;
-        for child in root.children() {
-            assert!(root.is_parent_of(&child));
+    // Offset the device tree by one byte so that its copy can't be aligned.
+    let mut offset = vec![0u8; FDT.len() + 1];
+    offset[1..].copy_from_slice(FDT);
+    let misaligned = &offset[1..];
+    assert_ne!(misaligned.as_ptr() as usize % 4, 0);
+
+    let owned = OwnedDevTree::from_slice(misaligned).unwrap();
+    assert_eq!(owned.as_bytes(), FDT);
+    assert_eq!(owned.as_bytes().as_ptr() as usize % 4, 0);
+    assert_eq!(owned.devtree().nodes().count(), Ok(DFS_NODES.len()));
+
+    let clone = owned.clone();
+    assert_eq!(clone.as_bytes(), FDT);
+    assert_eq!(clone.devtree().nodes().count(), Ok(DFS_NODES.len()));
+}
+
+#[test]
+fn raw_blob() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        assert_eq!(blob.raw(), Ok(FDT));
+
+        let fdt = AlignedFdt::new(64);
+        let blob = DevTree::new_in_larger_buffer(fdt.bytes()).unwrap();
+        assert_eq!(blob.raw().unwrap().len(), blob.totalsize());
+    }
+}
+
+#[test]
+fn strings_block() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let mut iter = blob.strings();
+        let mut next_offset = 0;
+        let mut found_compatible = false;
+        while let Some((offset, s)) = iter.next().unwrap() {
+            assert_eq!(offset, next_offset);
+            next_offset += s.len() + 1;
+            found_compatible |= s == "compatible";
+        }
+        assert_eq!(next_offset, blob.size_dt_strings() as usize);
+        assert!(found_compatible);
+
+        // Drop the terminator of the final string.
+        let mut fdt = AlignedFdt::new(0);
+        let end = blob.off_dt_strings() + blob.size_dt_strings() as usize;
+        fdt.bytes_mut()[end - 1] = b'x';
+        let blob = DevTree::new(fdt.bytes()).unwrap();
+        let mut iter = blob.strings();
+        let err = loop {
+            if let Err(e) = iter.next() {
+                break e;
+            }
+        };
+        assert_eq!(err, DevTreeError::ParseError);
+    }
+}
+
+#[test]
+fn nodes_iter() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let iter = blob.nodes();
+        let mut pair_iter = iter.zip(Fbi(DFS_NODES.iter()));
+        while let Some((node, expected)) = pair_iter.next().unwrap() {
+            assert_eq!(node.name().unwrap(), *expected);
+        }
+        assert!(iter.count().unwrap() == DFS_NODES.len());
+    }
+}
+
+#[test]
+fn nodes_iter_from_raw_pointer() {
+    unsafe {
+        let blob = DevTree::from_raw_pointer(&FDT[0] as *const u8).unwrap();
+        let iter = blob.nodes();
+        let mut pair_iter = iter.zip(Fbi(DFS_NODES.iter()));
+        while let Some((node, expected)) = pair_iter.next().unwrap() {
+            assert_eq!(node.name().unwrap(), *expected);
+        }
+        assert!(iter.count().unwrap() == DFS_NODES.len());
+    }
+}
+
+#[test]
+fn parse_iter_skip_current_subtree() {
+    use fdt_rs::base::parse::ParsedTok;
+
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let mut iter = blob.parse_iter();
+
+        // Find the cpus node and skip everything beneath it.
+        while let Some(tok) = iter.next().unwrap() {
+            if let ParsedTok::BeginNode(node) = tok {
+                if node.name == b"cpus" {
+                    break;
+                }
+            }
+        }
+        iter.skip_current_subtree().unwrap();
+
+        match iter.next().unwrap() {
+            Some(ParsedTok::BeginNode(node)) => assert_eq!(node.name, b"memory@80000000"),
+            _ => panic!("Expected the node following cpus."),
+        }
+
+        // Skipping the root node consumes the rest of the tree.
+        let mut iter = blob.parse_iter();
+        iter.next().unwrap();
+        iter.skip_current_subtree().unwrap();
+        assert!(iter.next().unwrap().is_none());
+        assert!(iter.skip_current_subtree().is_err());
+    }
+}
+
+#[test]
+fn parse_iter_position_and_seek() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let mut iter = blob.parse_iter();
+        iter.next().unwrap();
+        let pos = iter.position();
+        let first = iter.next().unwrap();
+
+        // Consume the rest of the tree, then rewind.
+        while iter.next().unwrap().is_some() {}
+        let end = iter.position();
+        iter.seek(pos).unwrap();
+        assert_eq!(iter.position(), pos);
+        assert_eq!(iter.next().unwrap(), first);
+
+        // The end of the structure block may be restored, after which nothing is parsed.
+        iter.seek(end).unwrap();
+        assert_eq!(iter.position(), end);
+        assert!(iter.next().unwrap().is_none());
+
+        // Positions past the end of the structure block are rejected.
+        let mut past = blob.parse_iter();
+        while past.next().unwrap().is_some() {}
+        past.offset += 4;
+        assert!(iter.seek(past.position()).is_err());
+        assert_eq!(iter.position(), end);
+    }
+}
+
+#[test]
+fn items_with_depth() {
+    use fdt_rs::base::DevTreeItem;
+
+    let idx = get_fdt_index();
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let mut pair_iter = blob
+            .items_with_depth()
+            .zip(Fbi(idx.index.items_with_depth()));
+        let mut count = 0;
+        while let Some(((item, depth), (expected, expected_depth))) = pair_iter.next().unwrap() {
+            assert_eq!(depth, expected_depth as isize);
+            match (item, expected) {
+                (DevTreeItem::Node(n), DevTreeIndexItem::Node(e)) => {
+                    assert_eq!(n.name().unwrap(), e.name().unwrap())
+                }
+                (DevTreeItem::Prop(p), DevTreeIndexItem::Prop(e)) => {
+                    assert_eq!(p.name().unwrap(), e.name().unwrap())
+                }
+                _ => panic!("Item kinds differ."),
+            }
+            count += 1;
+        }
+        assert_eq!(count, blob.items().count().unwrap());
+
+        let (root, depth) = blob.items_with_depth().next().unwrap().unwrap();
+        assert!(matches!(root, DevTreeItem::Node(_)));
+        assert_eq!(depth, 0);
+    }
+}
+
+#[test]
+fn node_child_count() {
+    let idx = get_fdt_index();
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let mut nodes = blob.nodes();
+        let mut expected = idx.index.nodes();
+        while let Some(node) = nodes.next().unwrap() {
+            let expected = expected.next().unwrap();
+            assert_eq!(node.child_count().unwrap(), expected.children().count());
+        }
+        assert_eq!(blob.root().unwrap().unwrap().child_count().unwrap(), 18);
+    }
+}
+
+#[test]
+fn iterators_are_copy() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let mut iter = blob.nodes();
+        iter.next().unwrap();
+
+        // Snapshot the position by assignment, then look ahead without disturbing it.
+        let mut lookahead = iter;
+        let next = lookahead.next().unwrap().unwrap();
+        assert!(iter.next().unwrap() == Some(next));
+    }
+}
+
+#[test]
+fn for_each_node() {
+    use core::ops::ControlFlow;
+
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let mut expected = blob.items_with_depth();
+        let mut count = 0;
+        blob.for_each_node(|node, depth| {
+            loop {
+                if let (DevTreeItem::Node(n), d) = expected.next()?.unwrap() {
+                    assert!(n == *node);
+                    assert_eq!(d, depth);
+                    break;
+                }
+            }
+            count += 1;
+            Ok(ControlFlow::Continue(()))
+        })
+        .unwrap();
+        assert_eq!(count, blob.nodes().count().unwrap());
+
+        // Stop at the first node beneath the root.
+        let mut visited = 0;
+        blob.for_each_node(|_, depth| {
+            visited += 1;
+            Ok(if depth > 0 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            })
+        })
+        .unwrap();
+        assert_eq!(visited, 2);
+
+        let res = blob.for_each_node(|_, _| Err(DevTreeError::NotFound));
+        assert_eq!(res, Err(DevTreeError::NotFound));
+    }
+}
+
+#[test]
+fn structural_events() {
+    use fdt_rs::base::StructEvent;
+
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let mut events = blob.structural_events();
+        let mut items = blob.items_with_depth();
+        let mut depth = -1;
+        let mut leaves = 0;
+        while let Some(event) = events.next().unwrap() {
+            let (item, item_depth) = match event {
+                StructEvent::Leave => {
+                    depth -= 1;
+                    leaves += 1;
+                    continue;
+                }
+                StructEvent::Enter(node) => {
+                    depth += 1;
+                    (DevTreeItem::Node(node), depth)
+                }
+                StructEvent::Property(prop) => (DevTreeItem::Prop(prop), depth),
+            };
+            assert!(items.next().unwrap() == Some((item, item_depth)));
+        }
+        assert!(items.next().unwrap().is_none());
+        assert_eq!(depth, -1);
+        assert_eq!(leaves, blob.nodes().count().unwrap());
+    }
+}
+
+#[test]
+fn node_parent() {
+    let idx = get_fdt_index();
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let mut nodes = blob.nodes();
+        let mut expected = idx.index.nodes();
+        while let Some(node) = nodes.next().unwrap() {
+            let parent = node.parent().unwrap().map(|p| p.name().unwrap());
+            let expected = expected.next().unwrap().parent().map(|p| p.name().unwrap());
+            assert_eq!(parent, expected);
+        }
+        assert!(blob.root().unwrap().unwrap().parent().unwrap().is_none());
+    }
+}
+
+// Test that comparision of props works as expected.
+#[test]
+fn verify_prop_comparisions() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+
+        let props_iter_1 = blob.props();
+        let props_iter_2 = blob.props();
+
+        let mut pair_iter = props_iter_1.zip(props_iter_2);
+        while let Some((prop_1, prop_2)) = pair_iter.next().unwrap() {
+            assert!(prop_1 == prop_2);
+        }
+
+        let mut props_iter_1 = blob.props();
+        let props_iter_2 = blob.props();
+
+        // Mess up the lock step iteration, every prop should be different
+        let _ = props_iter_1.next().unwrap();
+
+        let mut pair_iter = props_iter_1.zip(props_iter_2);
+        while let Some((prop_1, prop_2)) = pair_iter.next().unwrap() {
+            assert!(prop_1 != prop_2);
+        }
+    }
+}
+
+// Test that comparision of props works as expected.
+#[test]
+fn get_prop_node() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+
+        let prop = blob.props().next().unwrap().unwrap();
+        let node = prop.node();
+
+        assert_eq!(node.name().unwrap(), "");
+        assert!(prop.try_node().unwrap() == node);
+    }
+}
+
+// Test that comparision of props works as expected.
+#[test]
+fn get_memory_prop_node() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+
+        let mem_prop = blob
+            .props()
+            .find(|p| Ok(p.name()? == "device_type" && p.str()? == "memory"))
+            .unwrap()
+            .expect("Unable to find memory node.");
+        let mem_node = mem_prop.node();
+
+        let _ = mem_node
+            .props()
+            .find(|p| Ok(p.name()? == "reg"))
+            .unwrap()
+            .expect("Device tree memory node missing 'reg' prop.");
+    }
+}
+
+// Test that comparision of nodes works as expected.
+#[test]
+fn verify_node_comparisions() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+
+        let nodes_iter_1 = blob.nodes();
+        let nodes_iter_2 = blob.nodes();
+
+        let mut pair_iter = nodes_iter_1.zip(nodes_iter_2);
+        while let Some((node_1, node_2)) = pair_iter.next().unwrap() {
+            assert!(node_1 == node_2);
+        }
+
+        let mut nodes_iter_1 = blob.nodes();
+        let nodes_iter_2 = blob.nodes();
+
+        // Mess up the lock step iteration, every node should be different
+        let _ = nodes_iter_1.next().unwrap();
+
+        let mut pair_iter = nodes_iter_1.zip(nodes_iter_2);
+        while let Some((node_1, node_2)) = pair_iter.next().unwrap() {
+            assert!(node_1 != node_2);
+        }
+    }
+}
+
+#[test]
+fn node_prop_iter() {
+    unsafe {
+        let blob = DevTree::new(FDT).unwrap();
+        let mut node_iter = blob.nodes();
+        while let Some(node) = node_iter.next().unwrap() {
+            let mut prop_iter = node.props();
+            while let Some(prop) = prop_iter.next().unwrap() {
+                if prop.length() > 0 {
+                    if let Ok(i) = prop.iter_str().count() {
+                        if i == 0 {
+                            continue;
+                        }
+                        assert!(i < 64);
+
+                        let mut iter = prop.iter_str();
+                        while let Some(s) = iter.next().unwrap() {
+                            let _ = s;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn next_compatible_finds_initial_node() {
+    unsafe {
+        let fdt = DevTree::new(FDT).unwrap();
+        let node = fdt
+            .compatible_nodes("riscv-virtio")
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(node.name().unwrap() == ""); // Root node has no "name"
+    }
+}
+
+#[test]
+fn next_compatible_finds_final_node() {
+    unsafe {
+        let fdt = DevTree::new(FDT).unwrap();
+        let node = fdt
+            .compatible_nodes("riscv,clint0")
+            .next()
+            .unwrap()
+            .unwrap();
+        assert!(node.name().unwrap() == "clint@2000000");
+    }
+}
+
+#[test]
+fn find_all_compatible() {
+    unsafe {
+        let devtree = DevTree::new(FDT).unwrap();
+        let compat = "virtio,mmio";
+        let exp = "virtio_mmio@1000";
+        let mut count = 0;
+        let exp_count = 8;
+
+        if let Some(mut cur) = devtree.root().unwrap() {
+            while let Some(node) = cur.find_next_compatible_node(compat).unwrap() {
+                count += 1;
+                // Verify the prefix matches.
+                // (ascii doesn't have startswith)
+                assert!(node.name().unwrap()[0..exp.len()] == *exp);
+                cur = node;
+                assert!(count <= exp_count);
+            }
+        }
+        assert!(count == exp_count);
+    }
+}
+
+// Generic code should work the same on both base and index nodes.
+fn count_node_props<'dt, N: Node<'dt>>(node: &N) -> usize {
+    node.props().count().unwrap()
+}
+
+#[test]
+fn generic_node_trait() {
+    let idx = get_fdt_index();
+    let devtree = idx.index.fdt();
+
+    let mut base_nodes = devtree.nodes();
+    let mut index_nodes = idx.index.nodes();
+    while let Some(base_node) = base_nodes.next().unwrap() {
+        let index_node = index_nodes.next().unwrap();
+        assert_eq!(Node::name(&base_node), Node::name(&index_node));
+        assert_eq!(count_node_props(&base_node), count_node_props(&index_node));
+    }
+    assert!(index_nodes.next().is_none());
+
+    let root = idx.index.root();
+    let model = Node::prop(&root, "model").unwrap().unwrap();
+    assert_eq!(model.str().unwrap(), "riscv-virtio,qemu");
+    assert!(Node::prop(&root, "missing").unwrap().is_none());
+}
+
+pub mod index_tests {
+    use super::*;
+
+    // Test that we can create an index from a valid device tree
+    #[test]
+    fn create_index() {
+        let _ = get_fdt_index();
+    }
+
+    // Test that our index get_layout returns a usable layout size.
+    #[test]
+    fn create_sized_index() {
+        unsafe {
+            let devtree = DevTree::new(FDT).unwrap();
+            let layout = DevTreeIndex::get_layout(&devtree).unwrap();
+            let mut vec = vec![0u8; layout.size() + layout.align()];
+            DevTreeIndex::new(devtree, vec.as_mut_slice()).unwrap();
+        }
+    }
+
+    // Test that an invalid buffer size results in NotEnoughMemory on index allocation.
+    #[test]
+    fn expect_create_index_layout_fails_with_invalid_layout() {
+        unsafe {
+            let devtree = DevTree::new(FDT).unwrap();
+            let layout = DevTreeIndex::get_layout(&devtree).unwrap();
+            let mut vec = vec![0u8; layout.size() - 1];
+            DevTreeIndex::new(devtree, vec.as_mut_slice()).expect_err("Expected failure.");
+        }
+    }
+
+    // Test that a filtered index fits exactly within its filtered layout.
+    #[test]
+    fn filtered_index_fits_filtered_layout() {
+        let keep = |name: &[u8]| name != b"cpus" && name != b"soc";
+        unsafe {
+            let devtree = DevTree::new(FDT).unwrap();
+            let full = DevTreeIndex::get_layout(&devtree).unwrap();
+            let layout = DevTreeIndex::get_layout_filtered(&devtree, keep).unwrap();
+            assert!(layout.size() < full.size());
+
+            let mut vec = vec![0u8; layout.size() + layout.align()];
+            let start = vec.as_ptr().align_offset(layout.align());
+            let buf = &mut vec[start..start + layout.size()];
+            DevTreeIndex::new_filtered(devtree, &mut buf[..layout.size() - 1], keep)
+                .expect_err("Expected failure.");
+
+            let index = DevTreeIndex::new_filtered(devtree, buf, keep).unwrap();
+            assert!(index.node_at_path("/cpus").is_none());
+            assert!(index.node_at_path("/soc/pci").is_none());
+            assert_eq!(index.root().children().count(), 16);
+            assert!(index.node_at_path("/memory").is_some());
+            index.validate().unwrap();
+
+            // Pruned subtrees still lie within the bytes of their indexed ancestors.
+            let root = index.root().subtree_bytes();
+            assert_eq!(root, get_fdt_index().index.root().subtree_bytes());
+        }
+    }
+
+    #[test]
+    fn set_status_in_place() {
+        use fdt_rs::spec::NodeStatus;
+
+        let mut fdt = AlignedFdt::new(0);
+        let range = {
+            let idx = get_fdt_index();
+            let cpu = idx.index.node_at_path("/cpus/cpu@0").unwrap();
+            assert_eq!(
+                idx.index.root().status_value_range(),
+                Err(DevTreeError::NotFound)
+            );
+            cpu.status_value_range().unwrap()
+        };
+        let value = &mut fdt.bytes_mut()[range.clone()];
+        assert_eq!(value, b"okay\0");
+        assert_eq!(
+            NodeStatus::Disabled.write_value(value),
+            Err(DevTreeError::InvalidParameter(
+                "status does not fit the existing property"
+            ))
+        );
+        assert_eq!(value, b"okay\0");
+        NodeStatus::Fail.write_value(value).unwrap();
+
+        let index = &index_of(&fdt).index;
+        let cpu = index.node_at_path("/cpus/cpu@0").unwrap();
+        assert!(!cpu.is_enabled());
+        assert_eq!(cpu.try_prop("status").unwrap().unwrap().str(), Ok("fail"));
+    }
+
+    #[test]
+    fn node_status_from_value() {
+        use fdt_rs::spec::NodeStatus;
+
+        for status in &[
+            NodeStatus::Okay,
+            NodeStatus::Disabled,
+            NodeStatus::Reserved,
+            NodeStatus::Fail,
+        ] {
+            assert_eq!(NodeStatus::from_value(status.as_str()), Some(*status));
+        }
+        assert_eq!(NodeStatus::from_value("ok"), Some(NodeStatus::Okay));
+        assert_eq!(NodeStatus::from_value("fail-sss"), Some(NodeStatus::Fail));
+        assert_eq!(NodeStatus::from_value("failed"), None);
+        assert_eq!(NodeStatus::from_value("Okay"), None);
+    }
+
+    #[test]
+    fn reg_by_name() {
+        let idx = get_fdt_index();
+        let cpu = idx.index.node_at_path("/cpus/cpu@0").unwrap();
+        assert_eq!(cpu.reg_by_name("rv64imafdcsu"), Ok(None));
+
+        // Rename the cpu's `riscv,isa` property to `reg-names`, naming its only region.
+        let name = b"riscv,isa\0";
+        let off = FDT.windows(name.len()).position(|w| w == name).unwrap();
+        let mut fdt = AlignedFdt::new(0);
+        fdt.bytes_mut()[off..off + name.len()].copy_from_slice(b"reg-names\0");
+        let index = &index_of(&fdt).index;
+        let cpu = index.node_at_path("/cpus/cpu@0").unwrap();
+        assert_eq!(cpu.reg_by_name("rv64imafdcsu"), Ok(Some((0, 0))));
+        assert_eq!(cpu.reg_by_name("missing"), Ok(None));
+
+        let (prop, i) = cpu
+            .indexed_resource("reg", "reg-names", "rv64imafdcsu")
+            .unwrap()
+            .unwrap();
+        assert_eq!((prop.name(), i), (Ok("reg"), 0));
+        assert!(cpu
+            .indexed_resource("clocks", "reg-names", "rv64imafdcsu")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn compatible_rank() {
+        let idx = get_fdt_index();
+        let test = idx.index.node_at_path("/test").unwrap();
+        assert_eq!(test.compatible_rank("sifive,test1"), Some(0));
+        assert_eq!(test.compatible_rank("sifive,test0"), Some(1));
+        assert_eq!(test.compatible_rank("syscon"), Some(2));
+        assert_eq!(test.compatible_rank("sifive"), None);
+        assert_eq!(
+            idx.index
+                .node_at_path("/chosen")
+                .unwrap()
+                .compatible_rank("syscon"),
+            None
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn prop_map() {
+        let idx = get_fdt_index();
+        let map = idx
+            .index
+            .node_at_path("/memory")
+            .unwrap()
+            .prop_map()
+            .unwrap();
+        assert_eq!(map.len(), 2);
+        assert_eq!(map["device_type"], b"memory\0");
+        assert_eq!(map["reg"].len(), 16);
+    }
+
+    #[test]
+    fn subtree_bytes() {
+        let idx = get_fdt_index();
+        let fdt = idx.index.fdt();
+        for node in idx.index.nodes() {
+            let bytes = node.subtree_bytes();
+            assert_eq!(bytes[..4], [0, 0, 0, 1]);
+            assert_eq!(bytes[bytes.len() - 4..], [0, 0, 0, 2]);
+
+            // The next sibling, if any, immediately follows the subtree.
+            let end = node.to_handle().offset() + bytes.len();
+            let mut siblings = node.siblings_including_self().skip_while(|s| *s != node);
+            if let Some(sibling) = siblings.nth(1) {
+                assert_eq!(sibling.to_handle().offset(), end);
+            }
+        }
+
+        // The root spans the whole structure block except for its FDT_END token.
+        let root = idx.index.root().subtree_bytes();
+        assert_eq!(root.len(), fdt.size_dt_struct() as usize - 4);
+        let end = fdt.off_dt_struct() + root.len();
+        assert_eq!(fdt.buf()[end..end + 4], [0, 0, 0, 9]);
+    }
+
+    #[test]
+    fn for_each_compatible() {
+        let idx = get_fdt_index();
+        let mut names = Vec::new();
+        idx.index
+            .for_each_compatible("virtio,mmio", |node| names.push(node.name().unwrap()));
+        assert_eq!(names.len(), 8);
+        assert!(names.iter().all(|n| n.starts_with("virtio_mmio@")));
+
+        // Strings after the first in a compatible list match too.
+        let mut count = 0;
+        idx.index.for_each_compatible("syscon", |_| count += 1);
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn child_named() {
+        let idx = get_fdt_index();
+        let root = idx.index.root();
+        let soc = root.child_named("soc").unwrap();
+        assert!(soc == idx.index.node_at_path("/soc").unwrap());
+        assert!(root.child_named("memory@80000000").is_some());
+        // Unit addresses must match, and only direct children are considered.
+        assert!(root.child_named("memory").is_none());
+        assert!(root.child_named("cpu@0").is_none());
+        assert!(soc.child_named("pci@30000000").is_some());
+    }
+
+    #[test]
+    fn sibling_props() {
+        let idx = get_fdt_index();
+        let rtc = idx.index.node_at_path("/rtc").unwrap();
+        let props = rtc.props().collect::<Vec<_>>();
+        assert_eq!(props.len(), 4);
+        for prop in &props {
+            let siblings = prop.sibling_props().collect::<Vec<_>>();
+            assert_eq!(siblings.len(), 3);
+            assert!(!siblings.contains(prop));
+            assert!(siblings.iter().all(|p| props.contains(p)));
+        }
+    }
+
+    #[test]
+    fn frequency() {
+        let idx = get_fdt_index();
+        let cpus = idx.index.node_at_path("/cpus").unwrap();
+        assert_eq!(cpus.frequency("timebase-frequency"), Ok(Some(10_000_000)));
+        assert_eq!(cpus.frequency("clock-frequency"), Ok(None));
+
+        let uart = idx.index.node_at_path("/uart").unwrap();
+        assert_eq!(uart.frequency("clock-frequency"), Ok(Some(3_686_400)));
+        assert_eq!(uart.frequency("reg"), Err(DevTreeError::ParseError));
+    }
+
+    #[test]
+    fn prop_hash_set() {
+        use std::collections::HashSet;
+
+        let idx = get_fdt_index();
+        let mut seen = HashSet::new();
+        for prop in idx.index.props() {
+            assert!(seen.insert(prop));
+        }
+        let total = seen.len();
+        for prop in idx.index.props() {
+            assert!(!seen.insert(prop));
+        }
+        assert_eq!(seen.len(), total);
+        assert!(seen.contains(&idx.index.root().props().next().unwrap()));
+    }
+
+    #[test]
+    fn node_at_path_cached() {
+        use fdt_rs::index::cache::PathCache;
+
+        let idx = get_fdt_index();
+        let mut cache = PathCache::new();
+        let paths = [
+            "/",
+            "/soc/pci",
+            "/memory",
+            "/cpus/cpu@0",
+            "/chosen",
+            "/soc",
+            "/uart",
+            "/test",
+            "/cpus",
+            "/flash",
+            "/missing",
+        ];
+        // Resolve more paths than fit into the cache, twice, so entries are evicted and reused.
+        for _ in 0..2 {
+            for path in paths.iter().chain(paths.iter().rev()) {
+                let cached = idx.index.node_at_path_cached(&mut cache, path);
+                assert!(cached == idx.index.node_at_path(path), "{}", path);
+            }
+        }
+        let pci = idx.index.node_at_path_cached(&mut cache, "/soc/pci");
+        assert_eq!(pci.unwrap().name().unwrap(), "pci@30000000");
+    }
+
+    #[test]
+    fn diff_against() {
+        use fdt_rs::index::diff::DiffEvent;
+
+        let idx = get_fdt_index();
+        let mut count = 0;
+        idx.index.diff_against(&idx.index, |_| count += 1).unwrap();
+        assert_eq!(count, 0);
+
+        // Change the model and drop the cpus node in a copy of the tree.
+        let model = b"riscv-virtio,qemu\0";
+        let off = FDT.windows(model.len()).position(|w| w == model).unwrap();
+        let mut fdt = AlignedFdt::new(0);
+        fdt.bytes_mut()[off] = b'R';
+        let other = &filtered_index_of(&fdt, |name| name != b"cpus").index;
+
+        let mut events = Vec::new();
+        idx.index
+            .diff_against(other, |event| events.push(event))
+            .unwrap();
+        assert_eq!(events.len(), 2);
+        match &events[0] {
+            DiffEvent::PropChanged { old, new } => {
+                assert_eq!(old.name().unwrap(), "model");
+                assert_eq!(old.str().unwrap(), "riscv-virtio,qemu");
+                assert_eq!(new.str().unwrap(), "Riscv-virtio,qemu");
+                assert!(!props_value_eq(old, new));
+            }
+            _ => panic!("Expected a changed property."),
+        }
+        match &events[1] {
+            DiffEvent::NodeRemoved(node) => assert_eq!(node.name().unwrap(), "cpus"),
+            _ => panic!("Expected a removed node."),
+        }
+
+        events.clear();
+        other
+            .diff_against(&idx.index, |event| events.push(event))
+            .unwrap();
+        assert!(matches!(events[1], DiffEvent::NodeAdded(_)));
+    }
+
+    #[test]
+    fn semantically_eq() {
+        let idx = get_fdt_index();
+        assert!(idx.index.semantically_eq(&idx.index));
+
+        // A copy of the tree in another buffer is the same tree.
+        let fdt = AlignedFdt::new(0);
+        assert!(idx.index.semantically_eq(&index_of(&fdt).index));
+
+        // Point the root's #address-cells at a second copy of its name appended to the strings
+        // block. The blobs differ, the trees do not.
+        let (off_struct, off_strings, size_strings) = unsafe {
+            let devtree = DevTree::new(FDT).unwrap();
+            (
+                devtree.off_dt_struct(),
+                devtree.off_dt_strings(),
+                devtree.size_dt_strings(),
+            )
+        };
+        assert_eq!(off_strings + size_strings as usize, FDT.len());
+        let name = b"#address-cells\0";
+        let mut fdt = AlignedFdt::new(name.len());
+        let total = (FDT.len() + name.len()) as u32;
+        fdt.bytes_mut()[4..8].copy_from_slice(&total.to_be_bytes());
+        fdt.bytes_mut()[32..36].copy_from_slice(&(size_strings + name.len() as u32).to_be_bytes());
+        fdt.bytes_mut()[FDT.len()..].copy_from_slice(name);
+        // The root node begins the structure block. Its first property's nameoff follows the
+        // node's token and empty name, then the property's token and length.
+        let nameoff = off_struct + 16;
+        fdt.bytes_mut()[nameoff..nameoff + 4].copy_from_slice(&size_strings.to_be_bytes());
+        assert_ne!(fdt.bytes()[..FDT.len()], *FDT);
+        assert!(idx.index.semantically_eq(&index_of(&fdt).index));
+
+        // Inserting a NOP token after the root's name changes the blob, but not the tree.
+        let nop = 4u32.to_be_bytes();
+        let insert_at = off_struct + 8;
+        let mut fdt = AlignedFdt::new(nop.len());
+        let bytes = fdt.bytes_mut();
+        bytes[insert_at..insert_at + nop.len()].copy_from_slice(&nop);
+        bytes[insert_at + nop.len()..].copy_from_slice(&FDT[insert_at..]);
+        // Grow totalsize, off_dt_strings and size_dt_struct to match.
+        for &field in &[4, 12, 36] {
+            let mut value = [0; 4];
+            value.copy_from_slice(&FDT[field..field + 4]);
+            let value = u32::from_be_bytes(value) + nop.len() as u32;
+            bytes[field..field + 4].copy_from_slice(&value.to_be_bytes());
+        }
+        assert!(idx.index.semantically_eq(&index_of(&fdt).index));
+
+        // Replacing the root's first property with NOP tokens removes it.
+        let mut fdt = AlignedFdt::new(0);
+        for off in (off_struct + 8..off_struct + 24).step_by(4) {
+            fdt.bytes_mut()[off..off + 4].copy_from_slice(&4u32.to_be_bytes());
+        }
+        assert!(!idx.index.semantically_eq(&index_of(&fdt).index));
+
+        // A changed property value differs.
+        let model = b"riscv-virtio,qemu\0";
+        let off = FDT.windows(model.len()).position(|w| w == model).unwrap();
+        let mut fdt = AlignedFdt::new(0);
+        fdt.bytes_mut()[off] = b'R';
+        assert!(!idx.index.semantically_eq(&index_of(&fdt).index));
+        assert!(!index_of(&fdt).index.semantically_eq(&idx.index));
+    }
+
+    // Test DFS iteration using a DevTreeIndex.
+    #[test]
+    fn dfs_iteration() {
+        let idx = get_fdt_index();
+        test_index_dfs(&idx);
+    }
+
+    // Test that iteration over children works as expected.
+    #[test]
+    fn verify_root_children_iteration() {
+        let idx = get_fdt_index();
+        let root = idx.index.root();
+        assert_eq!(root.children().count(), 18);
+    }
+
+    // Test that comparision of nodes works as expected.
+    #[test]
+    fn verify_root_children_comparisions() {
+        let idx = get_fdt_index();
+        let root = idx.index.root();
+
+        let mut prev = None;
+        for child in root.children() {
+            assert!(root.is_parent_of(&child));
             if let Some(prev_child) = &prev {
                 assert!(child.is_sibling_of(prev_child));
                 assert!(prev_child != &child);
             }
-            prev = Some(child);
+            prev = Some(child);
+        }
+    }
+
+    #[test]
+    fn get_memory_prop_node() {
+        let idx = get_fdt_index();
+
+        let mem_prop = idx
+            .index
+            .props()
+            .find(|p| p.name() == Ok("device_type") && p.str() == Ok("memory"))
+            .expect("Unable to find memory node.");
+        let mem_node = mem_prop.node();
+
+        let _ = mem_node
+            .props()
+            .find(|p| p.name() == Ok("reg"))
+            .expect("Device tree memory node missing 'reg' prop.");
+    }
+
+    // Test that peeking returns the next item without advancing the iterator.
+    #[test]
+    fn peek_does_not_advance() {
+        let idx = get_fdt_index();
+        let mut iter = idx.index.items();
+
+        let peeked = iter.peek().unwrap();
+        assert!(peeked == iter.next().unwrap());
+
+        let peeked_prop = iter.peek_prop().unwrap();
+        assert_eq!(peeked_prop.name().unwrap(), "#address-cells");
+
+        let peeked_node = iter.peek_node().unwrap();
+        assert_eq!(peeked_node.name().unwrap(), DFS_NODES[1]);
+        assert!(iter.next_prop().unwrap() == peeked_prop);
+    }
+
+    #[test]
+    fn prop_iter_from() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/soc/pci@30000000").unwrap();
+        let names = node.props().map(|p| p.name().unwrap()).collect::<Vec<_>>();
+        assert!(names.len() > 3);
+
+        for start in 0..=names.len() {
+            let resumed = node
+                .prop_iter_from(start)
+                .map(|p| p.name().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(resumed, names[start..]);
+        }
+
+        // Indices past the last property yield nothing, rather than the next node's properties.
+        assert_eq!(node.prop_iter_from(names.len() + 5).count(), 0);
+    }
+
+    #[test]
+    fn devices_with_reg() {
+        let idx = get_fdt_index();
+        assert_eq!(idx.index.devices_with_reg().count(), 17);
+        assert!(idx.index.devices_with_reg().all(|d| d.is_ok()));
+
+        let regions = |path| {
+            let node = idx.index.node_at_path(path).unwrap();
+            let (_, reg) = idx
+                .index
+                .devices_with_reg()
+                .map(Result::unwrap)
+                .find(|(n, _)| *n == node)
+                .unwrap();
+            reg.map(|r| (r.address, r.size)).collect::<Vec<_>>()
+        };
+
+        assert_eq!(regions("/memory"), [(0x8000_0000, 0x800_0000)]);
+        assert_eq!(
+            regions("/flash"),
+            [(0x2000_0000, 0x200_0000), (0x2200_0000, 0x200_0000)]
+        );
+        assert_eq!(regions("/soc/clint"), [(0x200_0000, 0x1_0000)]);
+
+        // Cut the rtc's reg short of a whole region, keeping its padded length.
+        let rtc = idx.index.node_at_path("/rtc").unwrap();
+        let reg = Node::prop(&rtc, "reg").unwrap().unwrap();
+        let len_off = reg.raw().as_ptr() as usize - idx.index.fdt().buf().as_ptr() as usize - 8;
+        let mut fdt = AlignedFdt::new(0);
+        fdt.bytes_mut()[len_off..len_off + 4].copy_from_slice(&14u32.to_be_bytes());
+        let index = &index_of(&fdt).index;
+        let errors: Vec<_> = index.devices_with_reg().filter_map(Result::err).collect();
+        assert_eq!(errors, [DevTreeError::ParseError]);
+
+        // cpus uses one address cell and no size cells.
+        let cpu = idx.index.node_at_path("/cpus/cpu@0").unwrap();
+        let entry = cpu.reg().unwrap().next().unwrap();
+        assert_eq!((entry.address, entry.size), (0, 0));
+        assert_eq!(entry.address_cells.len(), 4);
+        assert!(entry.size_cells.is_empty());
+        assert_eq!(
+            (entry.address_u128(), entry.size_u128()),
+            (Some(0), Some(0))
+        );
+
+        assert_eq!(
+            idx.index.node_at_path("/chosen").unwrap().reg().err(),
+            Some(DevTreeError::NotFound)
+        );
+
+        let memory = idx.index.node_at_path("/memory").unwrap();
+        let (raw, address_cells, size_cells) = memory.reg_raw().unwrap().unwrap();
+        assert_eq!((raw.len(), address_cells, size_cells), (16, 2, 2));
+        let (raw, address_cells, size_cells) = cpu.reg_raw().unwrap().unwrap();
+        assert_eq!((raw, address_cells, size_cells), (&[0u8; 4][..], 1, 0));
+        assert!(idx
+            .index
+            .node_at_path("/chosen")
+            .unwrap()
+            .reg_raw()
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn node_handles() {
+        let idx = get_fdt_index();
+        let handles = idx.index.nodes().map(|n| n.to_handle()).collect::<Vec<_>>();
+
+        for (node, handle) in idx.index.nodes().zip(&handles) {
+            assert!(idx.index.node_from_handle(*handle).unwrap() == node);
+        }
+
+        // The root node begins the structure block.
+        assert_eq!(handles[0].offset(), idx.index.fdt().off_dt_struct());
+        let soc = idx.index.node_at_path("/soc").unwrap();
+        assert_eq!(soc.to_handle().offset(), 0x8cc);
+    }
+
+    #[test]
+    fn enabled_nodes() {
+        let idx = get_fdt_index();
+        assert_eq!(idx.index.enabled_nodes().count(), idx.index.nodes().count());
+
+        // Mark cpu@0 as failed. Its interrupt controller must be skipped along with it.
+        let mut fdt = AlignedFdt::new(0);
+        let status = FDT.windows(5).position(|w| w == b"okay\0").unwrap();
+        fdt.bytes_mut()[status..status + 4].copy_from_slice(b"fail");
+
+        let index = &index_of(&fdt).index;
+
+        let names = index
+            .enabled_nodes()
+            .map(|n| n.name().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(names.len(), index.nodes().count() - 2);
+        assert!(!names.contains(&"cpu@0"));
+        assert!(!names.contains(&"interrupt-controller"));
+
+        // Iteration continues with the node which follows the skipped subtree.
+        let cpus = names.iter().position(|&n| n == "cpus").unwrap();
+        assert_eq!(names[cpus + 4], "memory@80000000");
+    }
+
+    #[test]
+    fn subtree() {
+        let idx = get_fdt_index();
+        let cpus = idx.index.node_at_path("/cpus").unwrap();
+
+        let mut nodes = vec![];
+        let mut num_props = 0;
+        for item in cpus.subtree() {
+            match item {
+                DevTreeIndexItem::Node(n) => nodes.push(n.name().unwrap()),
+                DevTreeIndexItem::Prop(_) => num_props += 1,
+            }
+        }
+        assert_eq!(
+            nodes,
+            [
+                "cpus",
+                "cpu-map",
+                "cluster0",
+                "core0",
+                "cpu@0",
+                "interrupt-controller"
+            ]
+        );
+        assert_eq!(num_props, 3 + 1 + 7 + 4);
+
+        // A leaf's subtree is just itself and its properties.
+        let memory = idx.index.node_at_path("/memory").unwrap();
+        assert_eq!(memory.subtree().count(), 3);
+
+        // The root's subtree is the entire tree.
+        assert_eq!(
+            idx.index.root().subtree().count(),
+            idx.index.items().count()
+        );
+    }
+
+    // A mid-tree node's subtree iteration must not escape into its later siblings, while
+    // from_node_include continues on through the rest of the tree.
+    #[test]
+    fn subtree_does_not_escape() {
+        use fdt_rs::index::iters::DevTreeIndexIter;
+
+        let idx = get_fdt_index();
+        let cpu_map = idx.index.node_at_path("/cpus/cpu-map").unwrap();
+
+        fn names<'dt>(iter: DevTreeIndexIter<'_, '_, 'dt>) -> Vec<&'dt str> {
+            iter.filter_map(|item| match item {
+                DevTreeIndexItem::Node(n) => Some(n.name().unwrap()),
+                DevTreeIndexItem::Prop(_) => None,
+            })
+            .collect()
+        }
+        assert_eq!(names(cpu_map.subtree()), ["cpu-map", "cluster0", "core0"]);
+
+        let unbounded = names(DevTreeIndexIter::from_node_include(cpu_map.clone()));
+        assert_eq!(unbounded[..4], ["cpu-map", "cluster0", "core0", "cpu@0"]);
+        assert!(unbounded.contains(&"soc"));
+
+        // A leaf's subtree contains only the leaf and its own properties.
+        let rtc = idx.index.node_at_path("/rtc").unwrap();
+        assert_eq!(rtc.subtree().count(), 5);
+        assert!(rtc.subtree().all(|item| match item {
+            DevTreeIndexItem::Node(n) => n == rtc,
+            DevTreeIndexItem::Prop(p) => p.node() == rtc,
+        }));
+    }
+
+    #[test]
+    fn collect_props() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/test@100000").unwrap();
+
+        let names = ["reg", "status", "phandle"];
+        let mut out = [None, None, None];
+        node.collect_props(&names, &mut out).unwrap();
+        assert_eq!(out[0].as_ref().unwrap().length(), 16);
+        assert!(out[1].is_none());
+        assert_eq!(out[2].as_ref().unwrap().phandle(0), Ok(4));
+
+        let mut short = [None];
+        assert!(matches!(
+            node.collect_props(&names, &mut short),
+            Err(DevTreeError::InvalidParameter(_))
+        ));
+    }
+
+    #[test]
+    fn with_prop() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/test@100000").unwrap();
+
+        assert_eq!(node.with_prop("phandle", |p| p.phandle(0)), Some(Ok(4)));
+        assert_eq!(node.with_prop("reg", |p| p.length()), Some(16));
+        assert_eq!(node.with_prop("status", |p| p.length()), None);
+    }
+
+    #[test]
+    fn is_compatible() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/test@100000").unwrap();
+        assert!(node.is_compatible("sifive,test1"));
+        assert!(node.is_compatible("syscon"));
+        assert!(!node.is_compatible("sifive,test"));
+        assert!(!idx
+            .index
+            .node_at_path("/chosen")
+            .unwrap()
+            .is_compatible("syscon"));
+    }
+
+    #[test]
+    fn is_compatible_any() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/test@100000").unwrap();
+        assert!(node.is_compatible_any(&["ns16550a", "syscon"]));
+        assert!(node.is_compatible_any(&["sifive,test0"]));
+        assert!(!node.is_compatible_any(&["ns16550a", "sifive,test"]));
+        assert!(!node.is_compatible_any(&[]));
+        let chosen = idx.index.node_at_path("/chosen").unwrap();
+        assert!(!chosen.is_compatible_any(&["syscon"]));
+    }
+
+    #[test]
+    fn unique_compatible_nodes() {
+        let idx = get_fdt_index();
+        let names = |strings| {
+            idx.index
+                .unique_compatible_nodes(strings)
+                .map(|n| n.name().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        // test@100000 is compatible with all three strings, but is only returned once.
+        assert_eq!(
+            names(&["sifive,test0", "syscon", "sifive,test1"]),
+            ["test@100000"]
+        );
+        assert_eq!(
+            names(&["syscon", "riscv,clint0"]),
+            ["test@100000", "clint@2000000"]
+        );
+        assert_eq!(names(&["virtio,mmio"]).len(), 8);
+        assert!(names(&[]).is_empty());
+    }
+
+    #[test]
+    fn name_offsets() {
+        let idx = get_fdt_index();
+        let reg = idx.index.nameoff_of("reg").unwrap();
+        assert!(idx.index.nameoff_of("missing").is_none());
+
+        let by_offset = idx.index.props().filter(|p| p.name_offset() == reg).count();
+        let by_name = idx.index.props().filter(|p| p.name() == Ok("reg")).count();
+        assert_eq!(by_offset, 17);
+        assert_eq!(by_offset, by_name);
+    }
+
+    #[test]
+    fn clone_shares_index() {
+        let idx = get_fdt_index();
+        let clone = idx.index.clone();
+        assert_eq!(clone, idx.index);
+        assert_eq!(clone.root().name(), idx.index.root().name());
+        assert_eq!(clone.nodes().count(), idx.index.nodes().count());
+    }
+
+    #[test]
+    fn nodes_with_device_type() {
+        let idx = get_fdt_index();
+        let names = |dt| {
+            idx.index
+                .nodes_with_device_type(dt)
+                .map(|n| n.name().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(names("memory"), ["memory@80000000"]);
+        assert_eq!(names("cpu"), ["cpu@0"]);
+        assert_eq!(names("pci"), ["pci@30000000"]);
+        assert!(names("serial").is_empty());
+    }
+
+    #[test]
+    fn node_by_phandle() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_by_phandle(3).unwrap();
+        assert_eq!(node.name().unwrap(), "interrupt-controller@c000000");
+        assert!(idx.index.node_by_phandle(0x1234).is_none());
+    }
+
+    #[test]
+    fn node_at_path() {
+        let idx = get_fdt_index();
+        let path_name = |path| idx.index.node_at_path(path).map(|n| n.name().unwrap());
+
+        assert_eq!(path_name("/"), Some(""));
+        assert_eq!(path_name("/soc/pci@30000000"), Some("pci@30000000"));
+        assert_eq!(
+            path_name("/cpus/cpu@0/interrupt-controller"),
+            Some("interrupt-controller")
+        );
+        assert_eq!(path_name("/memory"), Some("memory@80000000"));
+        assert_eq!(path_name("/soc/missing"), None);
+        assert_eq!(path_name("soc"), None);
+        assert!(idx.index.alias("serial0").is_none());
+    }
+
+    #[test]
+    fn step_in_dfs() {
+        let idx = get_fdt_index();
+
+        let mut names = vec![];
+        let mut node = Some(idx.index.root());
+        while let Some(n) = node {
+            names.push(n.name().unwrap());
+            node = n.next_in_dfs();
+        }
+        assert_eq!(names, DFS_NODES);
+
+        let mut names = vec![];
+        let mut node = Some(idx.index.last_node());
+        while let Some(n) = node {
+            names.push(n.name().unwrap());
+            node = n.prev_in_dfs();
+        }
+        names.reverse();
+        assert_eq!(names, DFS_NODES);
+    }
+
+    #[test]
+    fn nodes_bfs() {
+        let idx = get_fdt_index();
+        let mut expected: Vec<_> = idx
+            .index
+            .items_with_depth()
+            .filter_map(|(item, depth)| match item {
+                DevTreeIndexItem::Node(n) => Some((depth, n.name().unwrap())),
+                _ => None,
+            })
+            .collect();
+        // Stable, so nodes of the same depth remain in DFS order.
+        expected.sort_by_key(|(depth, _)| *depth);
+
+        let bfs: Vec<_> = idx.index.nodes_bfs().map(|n| n.name().unwrap()).collect();
+        assert_eq!(bfs.len(), DFS_NODES.len());
+        assert!(bfs.iter().eq(expected.iter().map(|(_, name)| name)));
+    }
+
+    #[test]
+    fn nodes_at_depth() {
+        let idx = get_fdt_index();
+        for depth in 0..6 {
+            let expected: Vec<_> = idx
+                .index
+                .items_with_depth()
+                .filter_map(|(item, d)| match item {
+                    DevTreeIndexItem::Node(n) if d == depth => Some(n.name().unwrap()),
+                    _ => None,
+                })
+                .collect();
+            let names: Vec<_> = idx
+                .index
+                .nodes_at_depth(depth)
+                .map(|n| n.name().unwrap())
+                .collect();
+            assert_eq!(names, expected);
+        }
+        assert_eq!(idx.index.nodes_at_depth(0).count(), 1);
+        assert_eq!(idx.index.nodes_at_depth(1).count(), 18);
+        assert_eq!(idx.index.nodes_at_depth(4).count(), 1);
+        assert_eq!(idx.index.nodes_at_depth(5).count(), 0);
+    }
+
+    #[test]
+    fn dfs_with_stack() {
+        use core::ops::ControlFlow;
+
+        let idx = get_fdt_index();
+        let expected: Vec<_> = idx
+            .index
+            .items_with_depth()
+            .filter_map(|(item, depth)| match item {
+                DevTreeIndexItem::Node(n) => Some((depth, n.name().unwrap())),
+                _ => None,
+            })
+            .collect();
+
+        // The deepest node is /cpus/cpu-map/cluster0/core0, at a depth of 4.
+        let mut stack = [None, None, None, None, None];
+        let mut visited = vec![];
+        idx.index
+            .dfs_with_stack(&mut stack, |node, depth| {
+                visited.push((depth, node.name().unwrap()));
+                ControlFlow::Continue(())
+            })
+            .unwrap();
+        assert_eq!(visited, expected);
+
+        let mut stack = [None, None, None, None];
+        let res = idx
+            .index
+            .dfs_with_stack(&mut stack, |_, _| ControlFlow::Continue(()));
+        assert_eq!(res, Err(DevTreeError::LimitExceeded));
+
+        let mut count = 0;
+        idx.index
+            .dfs_with_stack(&mut [], |_, _| ControlFlow::Continue(()))
+            .unwrap_err();
+        idx.index
+            .dfs_with_stack(&mut stack, |_, _| {
+                count += 1;
+                if count == 3 {
+                    ControlFlow::Break(())
+                } else {
+                    ControlFlow::Continue(())
+                }
+            })
+            .unwrap();
+        assert_eq!(count, 3);
+    }
+
+    #[test]
+    fn relocatable_index() {
+        let fdt = AlignedFdt::new(0);
+        let FdtIndex {
+            index,
+            _vec: mut vec,
+        } = get_fdt_index();
+        let layout = DevTreeIndex::get_layout(index.fdt()).unwrap();
+        let reloc = index.into_relocatable();
+        let range = reloc.range_in(&vec).unwrap();
+        assert_eq!(range.len(), reloc.size());
+        assert!(reloc.size() <= layout.size());
+
+        // Move both the index and the device tree, then clobber the originals.
+        let mut moved = vec![0u64; reloc.size().div_ceil(8)];
+        let moved =
+            unsafe { core::slice::from_raw_parts_mut(moved.as_mut_ptr() as *mut u8, reloc.size()) };
+        moved.copy_from_slice(&vec[range]);
+        vec.iter_mut().for_each(|b| *b = 0xff);
+
+        let devtree = unsafe { DevTree::new(fdt.bytes()) }.unwrap();
+        let index = unsafe { reloc.rehydrate(moved, devtree) }.unwrap();
+        assert_eq!(index.validate(), Ok(()));
+        let names: Vec<_> = index.nodes().map(|n| n.name().unwrap()).collect();
+        assert_eq!(names, DFS_NODES);
+        let model = index.root().props().find(|p| p.name() == Ok("model"));
+        let model = model.unwrap().raw().as_ptr() as usize;
+        let start = fdt.bytes().as_ptr() as usize;
+        assert!(model > start && model < start + FDT.len());
+
+        // A different device tree is refused.
+        let mut moved = vec![0u64; reloc.size().div_ceil(8)];
+        let moved =
+            unsafe { core::slice::from_raw_parts_mut(moved.as_mut_ptr() as *mut u8, reloc.size()) };
+        let mut larger = AlignedFdt::new(8);
+        larger.bytes_mut()[4..8].copy_from_slice(&(FDT.len() as u32 + 8).to_be_bytes());
+        let devtree = unsafe { DevTree::new(larger.bytes()) }.unwrap();
+        assert!(unsafe { reloc.rehydrate(moved, devtree) }.is_err());
+
+        // Links which don't lie within the index are refused.
+        moved.iter_mut().for_each(|b| *b = 0xff);
+        let devtree = unsafe { DevTree::new(fdt.bytes()) }.unwrap();
+        assert_eq!(
+            unsafe { reloc.rehydrate(moved, devtree) }.err(),
+            Some(DevTreeError::InvalidIndex(
+                "Node link is outside of the relocated index"
+            ))
+        );
+    }
+
+    #[test]
+    fn unique_sibling_names() {
+        let idx = get_fdt_index();
+        assert!(idx.index.find_duplicate_sibling().is_none());
+        assert_eq!(idx.index.check_unique_sibling_names(), Ok(()));
+
+        // Rename virtio_mmio@10008000 to clash with its sibling virtio_mmio@10007000.
+        let name = b"virtio_mmio@10008000\0";
+        let off = FDT.windows(name.len()).position(|w| w == name).unwrap();
+        let mut fdt = AlignedFdt::new(0);
+        fdt.bytes_mut()[off + 16] = b'7';
+        let index = &index_of(&fdt).index;
+
+        let dup = index.find_duplicate_sibling().unwrap();
+        assert_eq!(dup.name(), Ok("virtio_mmio@10007000"));
+        assert!(dup.parent().unwrap() == index.root());
+        assert_eq!(
+            index.check_unique_sibling_names(),
+            Err(DevTreeError::DuplicateNodeName(dup.to_handle()))
+        );
+
+        // The error identifies the later sibling, which follows the renamed node.
+        let err = index.check_unique_sibling_names().unwrap_err();
+        match err {
+            DevTreeError::DuplicateNodeName(handle) => {
+                assert!(handle.offset() > off);
+                assert!(index.node_from_handle(handle).unwrap() == dup);
+            }
+            _ => panic!("Unexpected error {:?}", err),
         }
     }
 
     #[test]
-    fn get_memory_prop_node() {
+    fn validate() {
         let idx = get_fdt_index();
+        assert_eq!(idx.index.validate(), Ok(()));
+    }
 
-        let mem_prop = idx
+    #[test]
+    fn validate_corrupted_parent() {
+        let FdtIndex {
+            index,
+            _vec: mut vec,
+        } = get_fdt_index();
+        let devtree = *index.fdt();
+        let reloc = index.into_relocatable();
+        let range = reloc.range_in(&vec).unwrap();
+
+        // The index begins with the root node, so the only words holding its address are the
+        // parent links of its children. Clear the first of them.
+        let root = (vec.as_ptr() as usize + range.start).to_ne_bytes();
+        let word = core::mem::size_of::<usize>();
+        let parent = vec[range.clone()]
+            .chunks_exact(word)
+            .position(|w| w == root)
+            .unwrap();
+        let parent = range.start + parent * word;
+        vec[parent..parent + word].copy_from_slice(&0usize.to_ne_bytes());
+
+        // Restoring the index in place leaves every link pointing within it, so only the
+        // structural check notices the orphaned child.
+        let index = unsafe { reloc.rehydrate(&mut vec[range], devtree) }.unwrap();
+        assert_eq!(
+            index.validate(),
+            Err(DevTreeError::InvalidIndex(
+                "Child node does not point back to its parent"
+            ))
+        );
+    }
+
+    #[test]
+    fn required_lookups() -> Result<()> {
+        let idx = get_fdt_index();
+        let test = idx.index.node_at_path_required("/test@100000")?;
+        assert!(idx.index.node_by_phandle_required(4)? == test);
+
+        assert_eq!(
+            idx.index.node_at_path_required("/soc/missing").err(),
+            Some(DevTreeError::NotFound)
+        );
+        assert_eq!(
+            idx.index.node_by_phandle_required(0xdead).err(),
+            Some(DevTreeError::NotFound)
+        );
+        assert_eq!(
+            idx.index.alias_required("serial0").err(),
+            Some(DevTreeError::NotFound)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn siblings_including_self() {
+        let idx = get_fdt_index();
+        let root = idx.index.root();
+        assert!(root.siblings_including_self().eq(Some(root.clone())));
+
+        let chosen = idx.index.node_at_path("/chosen").unwrap();
+        assert!(chosen.siblings_including_self().eq(root.children()));
+        assert!(chosen.siblings_including_self().any(|n| n == chosen));
+
+        let core0 = idx
             .index
-            .props()
-            .find(|p| p.name() == Ok("device_type") && p.str() == Ok("memory"))
-            .expect("Unable to find memory node.");
-        let mem_node = mem_prop.node();
+            .node_at_path("/cpus/cpu-map/cluster0/core0")
+            .unwrap();
+        assert!(core0.siblings_including_self().eq(Some(core0.clone())));
+    }
 
-        let _ = mem_node
+    #[test]
+    fn try_prop() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/test@100000").unwrap();
+        let reg = node.try_prop("reg").unwrap().unwrap();
+        assert_eq!(reg.name(), Ok("reg"));
+        assert!(node.try_prop("missing").unwrap().is_none());
+    }
+
+    #[test]
+    fn descendant_props_named() {
+        let idx = get_fdt_index();
+
+        let soc = idx.index.node_at_path("/soc").unwrap();
+        let found: Vec<_> = soc
+            .descendant_props_named("interrupts-extended")
+            .map(|(node, prop)| {
+                assert!(prop.node() == node);
+                node.name().unwrap()
+            })
+            .collect();
+        assert_eq!(found, ["interrupt-controller@c000000", "clint@2000000"]);
+
+        // The subtree root's own properties are included, siblings' are not.
+        let cpus = idx.index.node_at_path("/cpus").unwrap();
+        let names = |prop| -> Vec<_> {
+            cpus.descendant_props_named(prop)
+                .map(|(node, _)| node.name().unwrap())
+                .collect()
+        };
+        assert_eq!(names("#address-cells"), ["cpus"]);
+        assert_eq!(names("phandle"), ["cpu@0", "interrupt-controller"]);
+
+        let all = idx
+            .index
+            .root()
+            .descendant_props_named("compatible")
+            .count();
+        let expected = idx
+            .index
             .props()
-            .find(|p| p.name() == Ok("reg"))
-            .expect("Device tree memory node missing 'reg' prop.");
+            .filter(|p| p.name() == Ok("compatible"))
+            .count();
+        assert_eq!(all, expected);
+    }
+
+    #[test]
+    fn props_with_prefix() {
+        let idx = get_fdt_index();
+        let cpu = idx.index.node_at_path("/cpus/cpu@0").unwrap();
+        let names: Vec<_> = cpu
+            .props_with_prefix("riscv,")
+            .map(|p| p.name().unwrap())
+            .collect();
+        assert!(!names.is_empty());
+        assert!(names.iter().all(|n| n.starts_with("riscv,")));
+        assert_eq!(
+            names.len(),
+            cpu.props()
+                .filter(|p| p.name().unwrap().starts_with("riscv,"))
+                .count()
+        );
+
+        assert_eq!(cpu.props_with_prefix("").count(), cpu.props().count());
+        assert_eq!(cpu.props_with_prefix("marvell,").count(), 0);
+    }
+
+    #[test]
+    fn common_ancestor() {
+        let idx = get_fdt_index();
+        let ancestor = |a: &str, b: &str| {
+            let a = idx.index.node_at_path(a).unwrap();
+            let b = idx.index.node_at_path(b).unwrap();
+            a.common_ancestor(&b).map(|n| n.name().unwrap())
+        };
+
+        assert_eq!(ancestor("/soc/pci", "/soc/clint"), Some("soc"));
+        assert_eq!(
+            ancestor(
+                "/cpus/cpu@0/interrupt-controller",
+                "/soc/interrupt-controller"
+            ),
+            Some("")
+        );
+        assert_eq!(
+            ancestor("/cpus", "/cpus/cpu@0/interrupt-controller"),
+            Some("cpus")
+        );
+        assert_eq!(ancestor("/chosen", "/chosen"), Some("chosen"));
+
+        let other = get_fdt_index();
+        let root = idx.index.root();
+        assert!(root.common_ancestor(&other.index.root()).is_none());
+    }
+
+    #[test]
+    fn effective_props() {
+        let idx = get_fdt_index();
+        let intc = idx
+            .index
+            .node_at_path("/cpus/cpu@0/interrupt-controller")
+            .unwrap();
+
+        let address_cells = intc.effective_prop("#address-cells").unwrap().unwrap();
+        assert_eq!(address_cells.node().name().unwrap(), "cpus");
+        assert!(intc.effective_prop("missing").unwrap().is_none());
+
+        let names: Vec<_> = intc.effective_props().map(|p| p.name().unwrap()).collect();
+        let mut deduped = names.clone();
+        deduped.sort_unstable();
+        deduped.dedup();
+        assert_eq!(names.len(), deduped.len());
+        assert!(names.contains(&"#address-cells"));
+        assert!(names.contains(&"model"));
+
+        // The closest definition wins.
+        let compatible = intc
+            .effective_props()
+            .find(|p| p.name() == Ok("compatible"))
+            .unwrap();
+        assert!(compatible.node() == intc);
+    }
+
+    #[test]
+    fn prop_as_node() {
+        let idx = get_fdt_index();
+        let chosen = idx.index.node_at_path("/chosen").unwrap();
+
+        let stdout = Node::prop(&chosen, "stdout-path").unwrap().unwrap();
+        let uart = stdout.as_node().unwrap().unwrap();
+        assert_eq!(uart.name().unwrap(), "uart@10000000");
+
+        // The compatible string is not a path, nor a known alias.
+        let compatible = Node::prop(&idx.index.root(), "compatible")
+            .unwrap()
+            .unwrap();
+        assert!(compatible.as_node().unwrap().is_none());
+    }
+
+    #[test]
+    fn follow_phandle() {
+        let idx = get_fdt_index();
+        let uart = idx.index.compatible_nodes("ns16550a").next().unwrap();
+
+        let parent = uart.follow_phandle("interrupt-parent").unwrap().unwrap();
+        assert_eq!(parent.name().unwrap(), "interrupt-controller@c000000");
+        assert!(uart.follow_phandle("power-domains").unwrap().is_none());
+
+        // Properties which don't hold a phandle are an error.
+        assert!(uart.follow_phandle("interrupts").is_err());
+    }
+
+    #[test]
+    fn decode_phandle_list() {
+        let idx = get_fdt_index();
+        let clint = idx.index.compatible_nodes("riscv,clint0").next().unwrap();
+
+        let mut iter = clint
+            .decode_phandle_list("interrupts-extended", "#interrupt-cells")
+            .unwrap();
+        let mut irqs = vec![];
+        while let Some(entry) = iter.next().unwrap() {
+            assert_eq!(
+                entry.node.as_ref().unwrap().name().unwrap(),
+                "interrupt-controller"
+            );
+            assert_eq!(entry.num_args(), 1);
+            irqs.push(entry.arg(0).unwrap());
+        }
+        assert_eq!(irqs, [3, 7]);
+
+        // Missing properties produce an empty iterator.
+        let mut iter = clint.decode_phandle_list("gpios", "#gpio-cells").unwrap();
+        assert!(iter.next().unwrap().is_none());
+
+        // Replace the first entry, <&cpu0_intc 3>, with two placeholder cells.
+        let propbuf = clint
+            .try_prop("interrupts-extended")
+            .unwrap()
+            .unwrap()
+            .raw();
+        let off = propbuf.as_ptr() as usize - idx.index.fdt().buf().as_ptr() as usize;
+        let mut fdt = AlignedFdt::new(0);
+        fdt.bytes_mut()[off..off + 8].fill(0);
+        let index = &index_of(&fdt).index;
+        let clint = index.compatible_nodes("riscv,clint0").next().unwrap();
+
+        let mut iter = clint
+            .decode_phandle_list("interrupts-extended", "#interrupt-cells")
+            .unwrap();
+        for _ in 0..2 {
+            let entry = iter.next().unwrap().unwrap();
+            assert!(entry.node.is_none());
+            assert_eq!(entry.num_args(), 0);
+        }
+        let entry = iter.next().unwrap().unwrap();
+        assert!(entry.node.is_some());
+        assert_eq!(entry.arg(0), Ok(7));
+        assert!(iter.next().unwrap().is_none());
+    }
+
+    #[test]
+    fn items_with_depth() {
+        let idx = get_fdt_index();
+
+        let mut nodes = 0;
+        let mut last_node_depth = 0;
+        for (item, depth) in idx.index.items_with_depth() {
+            match item {
+                DevTreeIndexItem::Node(node) => {
+                    let mut expected = 0;
+                    let mut cur = node.parent();
+                    while let Some(parent) = cur {
+                        expected += 1;
+                        cur = parent.parent();
+                    }
+                    assert_eq!(depth, expected, "{}", node.name().unwrap());
+                    last_node_depth = depth;
+                    nodes += 1;
+                }
+                DevTreeIndexItem::Prop(_) => assert_eq!(depth, last_node_depth),
+            }
+        }
+        assert_eq!(nodes, DFS_NODES.len());
+
+        let core0 = idx.index.items_with_depth().find(|(item, _)| match item {
+            DevTreeIndexItem::Node(n) => n.name().unwrap() == "core0",
+            _ => false,
+        });
+        assert_eq!(core0.unwrap().1, 4);
+    }
+
+    #[test]
+    fn read_cells() {
+        let idx = get_fdt_index();
+        let pci = idx
+            .index
+            .compatible_nodes("pci-host-ecam-generic")
+            .next()
+            .unwrap();
+
+        let reg = Node::prop(&pci, "reg").unwrap().unwrap();
+        let (addr, off) = reg.read_cells(0, 2).unwrap();
+        let (size, off) = reg.read_cells(off, 2).unwrap();
+        assert_eq!((addr, size, off), (0x3000_0000, 0x1000_0000, reg.length()));
+        reg.read_cells(0, 3).expect_err("Expected failure.");
+        reg.read_cells(off, 1).expect_err("Expected failure.");
+
+        // PCI child addresses are 3 cells wide.
+        let ranges = Node::prop(&pci, "ranges").unwrap().unwrap();
+        let (child, off) = ranges.read_cells_u128(0, 3).unwrap();
+        assert_eq!(child, 0x0100_0000_0000_0000_0000_0000);
+        assert_eq!(off, 12);
+        ranges.read_cells_u128(0, 5).expect_err("Expected failure.");
     }
 
     // Test iteration over the root nodes props.
@@ -370,6 +2376,322 @@ pub mod index_tests {
         test_prop_iteration(&get_fdt_index());
     }
 
+    #[test]
+    fn first_and_last_node() {
+        let idx = get_fdt_index();
+        assert!(idx.index.first_node() == idx.index.root());
+        assert!(idx.index.last_node() == idx.index.nodes().last().unwrap());
+        assert_eq!(
+            idx.index.last_node().name().unwrap(),
+            DFS_NODES[DFS_NODES.len() - 1]
+        );
+    }
+
+    #[test]
+    fn props_borrowed() {
+        let idx = get_fdt_index();
+        for node in idx.index.nodes() {
+            assert!(node.props().eq(node.props_borrowed()));
+        }
+    }
+
+    #[test]
+    fn guess_type() {
+        let idx = get_fdt_index();
+        let guess = |path: &str, prop: &str| {
+            let node = idx.index.node_at_path(path).unwrap();
+            let prop = node.props().find(|p| p.name().unwrap() == prop).unwrap();
+            prop.guess_type()
+        };
+
+        assert_eq!(guess("/", "compatible"), PropType::String);
+        assert_eq!(guess("/", "#address-cells"), PropType::Cells);
+        assert_eq!(guess("/test@100000", "compatible"), PropType::StringList);
+        assert_eq!(guess("/chosen", "bootargs"), PropType::Bytes);
+        assert_eq!(
+            guess("/soc/interrupt-controller", "interrupt-controller"),
+            PropType::Empty
+        );
+    }
+
+    #[test]
+    fn ranges() {
+        let idx = get_fdt_index();
+
+        let soc = idx.index.node_at_path("/soc").unwrap();
+        let ranges = soc.ranges().unwrap();
+        assert!(ranges.is_identity());
+        assert_eq!(ranges.count(), 0);
+
+        let pci = idx.index.node_at_path("/soc/pci").unwrap();
+        let ranges = pci.ranges().unwrap();
+        assert!(!ranges.is_identity());
+        let entries: Vec<_> = ranges.collect();
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].child_addr, 0);
+        assert_eq!(entries[0].parent_addr, 0x300_0000);
+        assert_eq!(entries[0].size, 0x1_0000);
+        assert_eq!(
+            entries[0].child_cells,
+            &[1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        );
+
+        assert_eq!(entries[1].child_addr, 0x4000_0000);
+        assert_eq!(entries[1].parent_addr, 0x4000_0000);
+        assert_eq!(entries[1].size, 0x4000_0000);
+        assert_eq!(entries[1].child_cells.len(), 12);
+        assert_eq!(entries[1].parent_cells.len(), 8);
+        assert_eq!(entries[1].size_cells.len(), 8);
+
+        // The u64 fields drop the PCI space code held in the first child cell.
+        assert_eq!(entries[0].child_addr_u128(), Some(0x100_0000 << 64));
+        assert_eq!(
+            entries[1].child_addr_u128(),
+            Some(0x200_0000 << 64 | 0x4000_0000)
+        );
+        assert_eq!(entries[1].parent_addr_u128(), Some(0x4000_0000));
+        assert_eq!(entries[1].size_u128(), Some(0x4000_0000));
+
+        let chosen = idx.index.node_at_path("/chosen").unwrap();
+        assert_eq!(chosen.ranges().err(), Some(DevTreeError::NotFound));
+    }
+
+    #[test]
+    fn prop_containing_node() {
+        let idx = get_fdt_index();
+        let intc = idx
+            .index
+            .node_at_path("/cpus/cpu@0/interrupt-controller")
+            .unwrap();
+        for prop in intc.props() {
+            assert!(prop.containing_node() == intc);
+            assert!(prop.containing_node() == prop.node());
+            assert_eq!(prop.node_name().unwrap(), "interrupt-controller");
+        }
+    }
+
+    #[test]
+    fn prop_type_predicates() {
+        let idx = get_fdt_index();
+        let prop = |path: &str, prop: &str| {
+            let node = idx.index.node_at_path(path).unwrap();
+            let found = node.props().find(|p| p.name().unwrap() == prop);
+            found.unwrap()
+        };
+
+        let compatible = prop("/test@100000", "compatible");
+        assert!(compatible.looks_like_string());
+        assert!(!compatible.looks_like_cells());
+        assert!(!compatible.is_empty());
+
+        let reg = prop("/test@100000", "reg");
+        assert!(reg.looks_like_cells());
+        assert!(!reg.looks_like_string());
+
+        let intc = prop("/soc/interrupt-controller", "interrupt-controller");
+        assert!(intc.is_empty());
+        assert!(!intc.looks_like_string());
+    }
+
+    #[test]
+    fn u32_at_cell() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/test@100000").unwrap();
+        let reg = Node::prop(&node, "reg").unwrap().unwrap();
+        assert_eq!(reg.cell_count(), 4);
+        assert_eq!(reg.u32_at_cell(1), Ok(0x10_0000));
+        assert_eq!(reg.u32_at_cell(3), Ok(0x1000));
+        assert_eq!(reg.u32_at_cell(4), Err(DevTreeError::InvalidOffset));
+    }
+
+    #[test]
+    fn cells_be() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/test@100000").unwrap();
+        let reg = Node::prop(&node, "reg").unwrap().unwrap();
+        let cells: Vec<u32> = reg.cells_be().unwrap().iter().map(|&c| c.into()).collect();
+        assert_eq!(cells, [0, 0x10_0000, 0, 0x1000]);
+
+        let chosen = idx.index.node_at_path("/chosen").unwrap();
+        let bootargs = Node::prop(&chosen, "bootargs").unwrap().unwrap();
+        assert_eq!(bootargs.cells_be().err(), Some(DevTreeError::ParseError));
+    }
+
+    #[test]
+    fn root_cells() {
+        let idx = get_fdt_index();
+        assert_eq!(idx.index.root_address_cells(), Ok(2));
+        assert_eq!(idx.index.root_size_cells(), Ok(2));
+
+        let test = idx.index.node_at_path("/test@100000").unwrap();
+        let (_, address_cells, size_cells) = test.reg_raw().unwrap().unwrap();
+        assert_eq!((address_cells, size_cells), (2, 2));
+
+        // The root of this tree has no cell counts, so the defaults apply.
+        let fdt = AlignedFdt::with_child("child");
+        let index = &index_of(&fdt).index;
+        assert_eq!(index.root_address_cells(), Ok(2));
+        assert_eq!(index.root_size_cells(), Ok(1));
+    }
+
+    #[test]
+    fn root_cells_malformed() {
+        let mut fdt = AlignedFdt::new(0);
+        let off_dt_struct = unsafe { DevTree::new(fdt.bytes()).unwrap().off_dt_struct() };
+
+        // Shrink the root's first prop, `#address-cells`, so it no longer holds a full cell. Its
+        // value is still padded to the same length, so the rest of the tree is unaffected.
+        let len_off = off_dt_struct + 12;
+        fdt.bytes_mut()[len_off..len_off + 4].copy_from_slice(&2u32.to_be_bytes());
+
+        let index = &index_of(&fdt).index;
+        assert_eq!(index.root_address_cells(), Err(DevTreeError::InvalidOffset));
+        assert_eq!(index.root_size_cells(), Ok(2));
+
+        let test = index.node_at_path("/test@100000").unwrap();
+        assert_eq!(test.reg_raw().err(), Some(DevTreeError::InvalidOffset));
+    }
+
+    #[test]
+    fn prop_ignore_ascii_case() {
+        let idx = get_fdt_index();
+        let uart = idx.index.node_at_path("/uart@10000000").unwrap();
+        let prop = uart.prop_ignore_ascii_case("Clock-Frequency").unwrap();
+        assert_eq!(prop.name(), Ok("clock-frequency"));
+        assert!(uart.prop_ignore_ascii_case("REG").is_some());
+        assert!(uart.prop_ignore_ascii_case("status").is_none());
+        assert!(uart.try_prop("Clock-Frequency").unwrap().is_none());
+    }
+
+    #[test]
+    fn raw_from() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/test@100000").unwrap();
+        let reg = Node::prop(&node, "reg").unwrap().unwrap();
+        assert_eq!(reg.raw_from(0), Ok(reg.raw()));
+        assert_eq!(reg.raw_from(8), Ok(&[0, 0, 0, 0, 0, 0, 0x10, 0][..]));
+        assert_eq!(reg.raw_from(16), Ok(&[][..]));
+        assert_eq!(reg.raw_from(17), Err(DevTreeError::InvalidOffset));
+    }
+
+    #[test]
+    fn str_exact() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/test@100000").unwrap();
+        let prop = |name| Node::prop(&node, name).unwrap().unwrap();
+
+        assert_eq!(
+            idx.index
+                .root()
+                .props()
+                .find(|p| p.name() == Ok("model"))
+                .unwrap()
+                .str_exact(),
+            Ok("riscv-virtio,qemu")
+        );
+        assert_eq!(prop("compatible").str(), Ok("sifive,test1"));
+        assert_eq!(
+            prop("compatible").str_exact(),
+            Err(DevTreeError::ParseError)
+        );
+        assert_eq!(prop("reg").str_exact(), Err(DevTreeError::ParseError));
+    }
+
+    #[test]
+    fn get_strlist() {
+        let idx = get_fdt_index();
+        let node = idx.index.node_at_path("/test@100000").unwrap();
+        let compatible = Node::prop(&node, "compatible").unwrap().unwrap();
+
+        let mut list = [""; 4];
+        assert_eq!(compatible.get_strlist(&mut list), Ok(3));
+        assert_eq!(list, ["sifive,test1", "sifive,test0", "syscon", ""]);
+
+        // A list which is too short is filled without overflowing, and the total is returned.
+        let mut list = [""; 2];
+        assert_eq!(compatible.get_strlist(&mut list), Ok(3));
+        assert_eq!(list, ["sifive,test1", "sifive,test0"]);
+        assert_eq!(compatible.get_strlist(&mut []), Ok(3));
+    }
+
+    #[test]
+    fn prop_len() {
+        let idx = get_fdt_index();
+        let uart = idx.index.node_at_path("/uart@10000000").unwrap();
+        assert_eq!(uart.prop_len("clock-frequency"), Some(4));
+        assert_eq!(uart.prop_len("reg"), Some(16));
+        assert_eq!(uart.prop_len("compatible"), Some(9));
+        assert_eq!(uart.prop_len("status"), None);
+
+        let soc = idx.index.node_at_path("/soc").unwrap();
+        assert_eq!(soc.prop_len("ranges"), Some(0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn prop_cells_vec() {
+        let idx = get_fdt_index();
+        let uart = idx.index.node_at_path("/uart@10000000").unwrap();
+        let prop = |name| uart.props().find(|p| p.name() == Ok(name)).unwrap();
+        assert_eq!(prop("clock-frequency").cells_vec(), Ok(vec![3686400]));
+        assert_eq!(prop("reg").cells_vec(), Ok(vec![0, 0x1000_0000, 0, 0x100]));
+        assert_eq!(
+            prop("compatible").cells_vec(),
+            Err(DevTreeError::ParseError)
+        );
+
+        let soc = idx.index.node_at_path("/soc").unwrap();
+        let ranges = soc.props().find(|p| p.name() == Ok("ranges")).unwrap();
+        assert_eq!(ranges.cells_vec(), Ok(vec![]));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn prop_str_lossy() {
+        let idx = get_fdt_index();
+        let compatible = idx
+            .index
+            .root()
+            .props()
+            .find(|p| p.name() == Ok("compatible"))
+            .unwrap();
+        assert_eq!(compatible.str_lossy(), "riscv-virtio");
+
+        let model = b"riscv-virtio,qemu\0";
+        let off = FDT.windows(model.len()).position(|w| w == model).unwrap();
+        let mut fdt = AlignedFdt::new(0);
+        fdt.bytes_mut()[off] = 0xff;
+        let index = &index_of(&fdt).index;
+        let prop = index
+            .root()
+            .props()
+            .find(|p| p.name() == Ok("model"))
+            .unwrap();
+        assert!(prop.str().is_err());
+        assert_eq!(prop.str_lossy(), "\u{fffd}iscv-virtio,qemu");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn snapshot() {
+        use fdt_rs::index::snapshot::PropValue;
+
+        let idx = get_fdt_index();
+        let snapshot = idx.index.root().snapshot().unwrap();
+        assert_eq!(snapshot.name, "");
+        assert_eq!(snapshot.children.len(), 18);
+        assert_eq!(
+            snapshot.props["compatible"],
+            PropValue::String("riscv-virtio")
+        );
+        assert_eq!(snapshot.props["#size-cells"], PropValue::Cells(vec![2]));
+
+        let json = serde_json::to_string(&snapshot).unwrap();
+        assert!(json.contains(r#""model":"riscv-virtio,qemu""#));
+        assert!(json.contains(r#""compatible":["sifive,test1","sifive,test0","syscon"]"#));
+    }
+
     pub fn test_prop_iteration(idx: &FdtIndex) {
         let iter = idx.index.props();
         assert_eq!(iter.count(), 105);